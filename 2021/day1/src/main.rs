@@ -1,36 +1,765 @@
+use std::env;
 use std::error::Error;
 use std::fs;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Measurment {
-    count: i32,
-    prev: i32,
+fn parse_depths(contents: &str) -> Result<Vec<i32>, Box<dyn Error>> {
+    let depths = contents
+        .trim()
+        .split('\n')
+        .map(|v| v.trim().parse::<i32>())
+        .collect::<Result<Vec<i32>, _>>()?;
+    Ok(depths)
+}
+
+// how many times the sum of `window` depths is bigger than the sum of the
+// window `gap` measurements before it.
+//
+// With a gap of 1 both windows share everything but their ends, so comparing
+// the sums is the same as comparing `depths[i + window] > depths[i]`. Bigger
+// gaps keep the difference between both sums up to date as they slide, so no
+// sums are ever stored.
+fn count_increases(depths: &[i32], window: usize, gap: usize) -> usize {
+    if window == 0 || gap == 0 || depths.len() < window + gap {
+        return 0;
+    }
+    if gap == 1 {
+        return depths
+            .iter()
+            .zip(depths.iter().skip(window))
+            .filter(|(prev, next)| next > prev)
+            .count();
+    }
+
+    let mut diff: i64 = (0..window)
+        .map(|j| depths[j + gap] as i64 - depths[j] as i64)
+        .sum();
+    let mut count = usize::from(diff > 0);
+    for i in 1..=depths.len() - window - gap {
+        diff += depths[i + gap + window - 1] as i64 - depths[i + gap - 1] as i64;
+        diff -= depths[i + window - 1] as i64 - depths[i - 1] as i64;
+        if diff > 0 {
+            count += 1;
+        }
+    }
+    count
+}
+
+// same count as `count_increases`, but fed one depth at a time. Only the last
+// `window + gap` depths are kept, in a ring buffer, together with the sum of the
+// latest window and of the window `gap` measurements before it.
+#[derive(Debug)]
+struct SonarSweep {
+    window: usize,
+    gap: usize,
+    ring: Vec<i32>,
+    seen: usize,
+    current: i64,
+    previous: i64,
+    count: usize,
+}
+
+impl SonarSweep {
+    fn new(window: usize, gap: usize) -> Self {
+        Self {
+            window,
+            gap,
+            ring: vec![0; window + gap],
+            seen: 0,
+            current: 0,
+            previous: 0,
+            count: 0,
+        }
+    }
+
+    // depth that arrived `back` measurements before the one being pushed
+    fn back(&self, back: usize) -> i64 {
+        self.ring[(self.seen - back) % self.ring.len()] as i64
+    }
+
+    fn push(&mut self, depth: i32) {
+        if self.window == 0 || self.gap == 0 {
+            self.seen += 1;
+            return;
+        }
+        let t = self.seen;
+        self.current += depth as i64;
+        if t >= self.window {
+            self.current -= self.back(self.window);
+        }
+        if t >= self.gap {
+            self.previous += self.back(self.gap);
+        }
+        if t >= self.gap + self.window {
+            self.previous -= self.back(self.gap + self.window);
+        }
+        if t + 1 >= self.window + self.gap && self.current > self.previous {
+            self.count += 1;
+        }
+
+        let slot = t % self.ring.len();
+        self.ring[slot] = depth;
+        self.seen += 1;
+    }
+}
+
+// reads one depth per line, reusing a single line buffer. Every `every` lines
+// the running count is written to `progress`.
+fn sweep<R: BufRead, W: Write>(
+    mut reader: R,
+    window: usize,
+    gap: usize,
+    every: usize,
+    progress: &mut W,
+) -> Result<SonarSweep, Box<dyn Error>> {
+    let mut sonar = SonarSweep::new(window, gap);
+    let mut line = String::new();
+    let mut line_number = 0;
+    while reader.read_line(&mut line)? > 0 {
+        line_number += 1;
+        let value = line.trim();
+        if !value.is_empty() {
+            let depth = value
+                .parse::<i32>()
+                .map_err(|err| format!("line {line_number}: {err}"))?;
+            sonar.push(depth);
+        }
+        if every > 0 && line_number % every == 0 {
+            writeln!(progress, "line {line_number}: {} increases", sonar.count)?;
+        }
+        line.clear();
+    }
+    Ok(sonar)
+}
+
+// line ranges are 1-based and inclusive, like the lines of the input
+#[derive(Debug, PartialEq)]
+struct Run {
+    start: usize,
+    end: usize,
+}
+
+impl Run {
+    fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Plateau {
+    start: usize,
+    end: usize,
+    sum: i64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Extreme {
+    depth: i32,
+    line: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct Profile {
+    window: usize,
+    increasing: Option<Run>,
+    decreasing: Option<Run>,
+    plateaus: Vec<Plateau>,
+    min: Extreme,
+    max: Extreme,
+}
+
+// longest stretch where every depth keeps `holds` with the one before it, the
+// first one wins on ties
+fn longest_run(depths: &[i32], holds: impl Fn(i32, i32) -> bool) -> Option<Run> {
+    let mut best: Option<Run> = None;
+    let mut start = 0;
+    for i in 1..=depths.len() {
+        if i < depths.len() && holds(depths[i - 1], depths[i]) {
+            continue;
+        }
+        let run = Run {
+            start: start + 1,
+            end: i,
+        };
+        if run.len() > 1 && best.as_ref().is_none_or(|b| run.len() > b.len()) {
+            best = Some(run);
+        }
+        start = i;
+    }
+    best
+}
+
+// stretches of consecutive windows with the same sum. Like in
+// `count_increases`, two neighbour windows are equal when
+// `depths[i + window] == depths[i]`.
+fn plateaus(depths: &[i32], window: usize) -> Vec<Plateau> {
+    if window == 0 || depths.len() <= window {
+        return Vec::new();
+    }
+    let plateau = |first: usize, last: usize| Plateau {
+        start: first + 1,
+        end: last + window,
+        sum: depths[first..first + window]
+            .iter()
+            .map(|d| *d as i64)
+            .sum(),
+    };
+
+    let mut out = Vec::new();
+    let mut first = None;
+    for i in 0..depths.len() - window {
+        if depths[i + window] == depths[i] {
+            first.get_or_insert(i);
+        } else if let Some(first) = first.take() {
+            out.push(plateau(first, i));
+        }
+    }
+    if let Some(first) = first {
+        out.push(plateau(first, depths.len() - window));
+    }
+    out
+}
+
+fn profile(depths: &[i32], window: usize) -> Option<Profile> {
+    let extreme = |(line, depth): (usize, &i32)| Extreme {
+        depth: *depth,
+        line: line + 1,
+    };
+    // report the first line of each, `max_by_key` would keep the last maximum
+    let min = depths.iter().enumerate().min_by_key(|(_, d)| **d)?;
+    let max = depths.iter().enumerate().rev().max_by_key(|(_, d)| **d)?;
+
+    Some(Profile {
+        window,
+        increasing: longest_run(depths, |prev, next| next > prev),
+        decreasing: longest_run(depths, |prev, next| next < prev),
+        plateaus: plateaus(depths, window),
+        min: extreme(min),
+        max: extreme(max),
+    })
+}
+
+impl Profile {
+    fn to_text(&self) -> String {
+        let run = |run: &Option<Run>| match run {
+            Some(run) => format!("{} depths, lines {}-{}", run.len(), run.start, run.end),
+            None => "none".to_string(),
+        };
+        let mut out = format!(
+            "min depth: {} (line {})\nmax depth: {} (line {})\n",
+            self.min.depth, self.min.line, self.max.depth, self.max.line
+        );
+        out.push_str(&format!(
+            "longest increasing run: {}\n",
+            run(&self.increasing)
+        ));
+        out.push_str(&format!(
+            "longest decreasing run: {}\n",
+            run(&self.decreasing)
+        ));
+        out.push_str(&format!(
+            "plateaus (window {}): {}\n",
+            self.window,
+            self.plateaus.len()
+        ));
+        for plateau in self.plateaus.iter() {
+            out.push_str(&format!(
+                "  lines {}-{}: sum {}\n",
+                plateau.start, plateau.end, plateau.sum
+            ));
+        }
+        out
+    }
+
+    fn to_json(&self) -> String {
+        let run = |run: &Option<Run>| match run {
+            Some(run) => format!(
+                r#"{{"start":{},"end":{},"length":{}}}"#,
+                run.start,
+                run.end,
+                run.len()
+            ),
+            None => "null".to_string(),
+        };
+        let plateaus: Vec<String> = self
+            .plateaus
+            .iter()
+            .map(|p| format!(r#"{{"start":{},"end":{},"sum":{}}}"#, p.start, p.end, p.sum))
+            .collect();
+        format!(
+            r#"{{"min":{{"depth":{},"line":{}}},"max":{{"depth":{},"line":{}}},"increasing":{},"decreasing":{},"window":{},"plateaus":[{}]}}"#,
+            self.min.depth,
+            self.min.line,
+            self.max.depth,
+            self.max.line,
+            run(&self.increasing),
+            run(&self.decreasing),
+            self.window,
+            plateaus.join(",")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GlitchDetector {
+    // further than `threshold` from the median of the `radius` readings on
+    // each side
+    Median { radius: usize, threshold: f64 },
+    // further than `threshold` standard deviations from the mean of the
+    // `radius` readings on each side. When the neighbours are all equal any
+    // difference is a glitch.
+    ZScore { radius: usize, threshold: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GlitchRepair {
+    Exclude,
+    Interpolate,
+}
+
+#[derive(Debug, PartialEq)]
+struct Glitch {
+    line: usize,
+    depth: i32,
+    expected: f64,
+}
+
+fn median(values: &mut [i32]) -> f64 {
+    values.sort();
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[middle - 1] as f64 + values[middle] as f64) / 2.0,
+        _ => values[middle] as f64,
+    }
+}
+
+// the reading itself is left out of its neighbourhood, so a spike can't hide
+// itself by pulling the median or the mean towards it
+fn find_glitches(depths: &[i32], detector: GlitchDetector) -> Vec<Glitch> {
+    let radius = match detector {
+        GlitchDetector::Median { radius, .. } | GlitchDetector::ZScore { radius, .. } => radius,
+    };
+    let mut glitches = Vec::new();
+    for (i, depth) in depths.iter().enumerate() {
+        let mut neighbours: Vec<i32> = depths[i.saturating_sub(radius)..i]
+            .iter()
+            .chain(depths[i + 1..(i + radius + 1).min(depths.len())].iter())
+            .copied()
+            .collect();
+        if neighbours.is_empty() {
+            continue;
+        }
+        let depth_f = *depth as f64;
+        let (expected, is_glitch) = match detector {
+            GlitchDetector::Median { threshold, .. } => {
+                let expected = median(&mut neighbours);
+                (expected, (depth_f - expected).abs() > threshold)
+            }
+            GlitchDetector::ZScore { threshold, .. } => {
+                let len = neighbours.len() as f64;
+                let mean = neighbours.iter().map(|d| *d as f64).sum::<f64>() / len;
+                let variance = neighbours
+                    .iter()
+                    .map(|d| (*d as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / len;
+                let deviation = (depth_f - mean).abs();
+                let std = variance.sqrt();
+                match std > 0.0 {
+                    true => (mean, deviation / std > threshold),
+                    false => (mean, deviation > 0.0),
+                }
+            }
+        };
+        if is_glitch {
+            glitches.push(Glitch {
+                line: i + 1,
+                depth: *depth,
+                expected,
+            });
+        }
+    }
+    glitches
+}
+
+// depths with the glitches dropped, or replaced by a straight line between the
+// closest good readings around them
+fn repair_glitches(depths: &[i32], glitches: &[Glitch], repair: GlitchRepair) -> Vec<i32> {
+    let mut flagged = vec![false; depths.len()];
+    glitches.iter().for_each(|g| flagged[g.line - 1] = true);
+
+    match repair {
+        GlitchRepair::Exclude => depths
+            .iter()
+            .zip(flagged.iter())
+            .filter(|(_, flagged)| !**flagged)
+            .map(|(depth, _)| *depth)
+            .collect(),
+        GlitchRepair::Interpolate => {
+            let good = |i: &usize| !flagged[*i];
+            (0..depths.len())
+                .map(|i| {
+                    if !flagged[i] {
+                        return depths[i];
+                    }
+                    let before = (0..i).rev().find(good);
+                    let after = (i + 1..depths.len()).find(good);
+                    match (before, after) {
+                        (Some(b), Some(a)) => {
+                            let step = (depths[a] as f64 - depths[b] as f64) / (a - b) as f64;
+                            (depths[b] as f64 + step * (i - b) as f64).round() as i32
+                        }
+                        (Some(b), None) => depths[b],
+                        (None, Some(a)) => depths[a],
+                        (None, None) => depths[i],
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn flag_value<T>(args: &[String], name: &str) -> Result<Option<T>, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Error + 'static,
+{
+    match args.iter().position(|arg| arg == name) {
+        Some(i) => {
+            let value = args.get(i + 1).ok_or(format!("{name} needs a value"))?;
+            Ok(Some(value.parse::<T>()?))
+        }
+        None => Ok(None),
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let window = flag_value::<usize>(&args, "--window")?;
+    let gap = flag_value::<usize>(&args, "--gap")?;
+
+    if args.first().map(String::as_str) == Some("stream") {
+        let every = flag_value(&args, "--every")?.unwrap_or(1_000_000);
+        let (window, gap) = (window.unwrap_or(1), gap.unwrap_or(1));
+        let sonar = sweep(io::stdin().lock(), window, gap, every, &mut io::stderr())?;
+        println!("Items (window {window}, gap {gap}): {}", sonar.count);
+        return Ok(());
+    }
+
     let contents = fs::read_to_string("input.txt")?;
+    let depths = parse_depths(&contents)?;
 
-    let parsed_m: Vec<i32> = contents
-        .trim()
-        .split('\n')
-        .map(|v| v.parse::<i32>().unwrap())
-        .collect();
+    if args.first().map(String::as_str) == Some("profile") {
+        let profile = profile(&depths, window.unwrap_or(3)).ok_or("no depths to profile")?;
+        match args.iter().any(|arg| arg == "--json") {
+            true => println!("{}", profile.to_json()),
+            false => print!("{}", profile.to_text()),
+        }
+        return Ok(());
+    }
 
-    let sum_m: Vec<i32> = parsed_m.windows(3).map(|v| v.iter().sum()).collect();
+    if args.first().map(String::as_str) == Some("glitches") {
+        let threshold = flag_value::<f64>(&args, "--threshold")?;
+        let detector = match flag_value::<usize>(&args, "--zscore")? {
+            Some(radius) => GlitchDetector::ZScore {
+                radius,
+                threshold: threshold.unwrap_or(3.0),
+            },
+            None => GlitchDetector::Median {
+                radius: flag_value(&args, "--median")?.unwrap_or(3),
+                threshold: threshold.unwrap_or(100.0),
+            },
+        };
+        let glitches = find_glitches(&depths, detector);
+        println!("glitches: {}", glitches.len());
+        for glitch in glitches.iter() {
+            println!(
+                "  line {}: {} (expected {:.1})",
+                glitch.line, glitch.depth, glitch.expected
+            );
+        }
 
-    let mut m = Measurment {
-        count: 0,
-        prev: *sum_m.first().unwrap(),
-    };
-    let measurment = sum_m.iter().fold(m, |acc, next| {
-        let count = match next > &acc.prev {
-            true => acc.count + 1,
-            _ => acc.count,
-        };
-        m.count = count;
-        m.prev = *next;
-        m
-    });
-    println!("Items: {}", measurment.count);
+        let repair = match (
+            args.iter().any(|arg| arg == "--exclude"),
+            args.iter().any(|arg| arg == "--interpolate"),
+        ) {
+            (true, false) => GlitchRepair::Exclude,
+            (false, true) => GlitchRepair::Interpolate,
+            (false, false) => return Ok(()),
+            (true, true) => return Err("pick one of --exclude or --interpolate".into()),
+        };
+        let (window, gap) = (window.unwrap_or(1), gap.unwrap_or(1));
+        let repaired = repair_glitches(&depths, &glitches, repair);
+        println!(
+            "Items (window {window}, gap {gap}): {} -> {}",
+            count_increases(&depths, window, gap),
+            count_increases(&repaired, window, gap)
+        );
+        return Ok(());
+    }
+
+    if window.is_some() || gap.is_some() {
+        let (window, gap) = (window.unwrap_or(1), gap.unwrap_or(1));
+        let count = count_increases(&depths, window, gap);
+        println!("Items (window {window}, gap {gap}): {count}");
+        return Ok(());
+    }
+
+    println!("Items: {}", count_increases(&depths, 1, 1));
+    println!("Items (window 3): {}", count_increases(&depths, 3, 1));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    // the straightforward way: build every window sum and compare them
+    fn count_increases_naive(depths: &[i32], window: usize, gap: usize) -> usize {
+        let sums: Vec<i64> = depths
+            .windows(window)
+            .map(|v| v.iter().map(|d| *d as i64).sum())
+            .collect();
+        sums.iter()
+            .zip(sums.iter().skip(gap))
+            .filter(|(prev, next)| next > prev)
+            .count()
+    }
+
+    #[test]
+    fn test_parse_depths() {
+        let output = parse_depths("199\n200\n208\n").unwrap();
+        assert_eq!(output, vec![199, 200, 208]);
+
+        assert!(parse_depths("199\nabc\n208").is_err());
+    }
+
+    #[test]
+    fn test_count_increases() {
+        assert_eq!(count_increases(&SAMPLE, 1, 1), 7);
+        assert_eq!(count_increases(&SAMPLE, 3, 1), 5);
+    }
+
+    #[test]
+    fn test_count_increases_edges() {
+        assert_eq!(count_increases(&SAMPLE, 0, 1), 0);
+        assert_eq!(count_increases(&SAMPLE, 1, 0), 0);
+        assert_eq!(count_increases(&SAMPLE, 10, 1), 0);
+        assert_eq!(count_increases(&SAMPLE, 9, 1), 1);
+        assert_eq!(count_increases(&[], 1, 1), 0);
+    }
+
+    #[test]
+    fn test_sonar_sweep_matches_count_increases() {
+        for window in 0..=5 {
+            for gap in 0..=6 {
+                let mut sonar = SonarSweep::new(window, gap);
+                SAMPLE.iter().for_each(|depth| sonar.push(*depth));
+                assert_eq!(
+                    sonar.count,
+                    count_increases(&SAMPLE, window, gap),
+                    "window {window}, gap {gap}"
+                );
+                assert_eq!(sonar.ring.len(), window + gap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sweep() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let mut progress = Vec::new();
+        let sonar = sweep(input.as_bytes(), 3, 1, 4, &mut progress).unwrap();
+        assert_eq!(sonar.count, 5);
+        assert_eq!(
+            String::from_utf8(progress).unwrap(),
+            "line 4: 1 increases\nline 8: 3 increases\n"
+        );
+
+        let input = "199\n200\n\n2o8\n";
+        let output = sweep(input.as_bytes(), 1, 1, 0, &mut io::sink());
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "line 4: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_profile() {
+        let expected = Profile {
+            window: 3,
+            increasing: Some(Run { start: 1, end: 4 }),
+            decreasing: Some(Run { start: 4, end: 5 }),
+            plateaus: vec![Plateau {
+                start: 2,
+                end: 5,
+                sum: 618,
+            }],
+            min: Extreme {
+                depth: 199,
+                line: 1,
+            },
+            max: Extreme {
+                depth: 269,
+                line: 8,
+            },
+        };
+        assert_eq!(profile(&SAMPLE, 3), Some(expected));
+        assert_eq!(profile(&[], 3), None);
+    }
+
+    #[test]
+    fn test_longest_run() {
+        assert_eq!(longest_run(&[5, 5, 5], |prev, next| next > prev), None);
+        assert_eq!(
+            longest_run(&[3, 1, 2, 3, 0, 1, 2, 3], |prev, next| next > prev),
+            Some(Run { start: 5, end: 8 })
+        );
+    }
+
+    #[test]
+    fn test_plateaus() {
+        let depths = [1, 2, 1, 2, 1, 2, 7, 3, 3, 3];
+        let expected = vec![
+            Plateau {
+                start: 1,
+                end: 6,
+                sum: 3,
+            },
+            Plateau {
+                start: 8,
+                end: 10,
+                sum: 6,
+            },
+        ];
+        assert_eq!(plateaus(&depths, 2), expected);
+        assert_eq!(plateaus(&depths, 0), vec![]);
+        assert_eq!(plateaus(&depths, 10), vec![]);
+    }
+
+    #[test]
+    fn test_profile_output() {
+        let profile = profile(&SAMPLE, 3).unwrap();
+        let expected = "min depth: 199 (line 1)
+max depth: 269 (line 8)
+longest increasing run: 4 depths, lines 1-4
+longest decreasing run: 2 depths, lines 4-5
+plateaus (window 3): 1
+  lines 2-5: sum 618
+";
+        assert_eq!(profile.to_text(), expected);
+
+        let expected = r#"{"min":{"depth":199,"line":1},"max":{"depth":269,"line":8},"increasing":{"start":1,"end":4,"length":4},"decreasing":{"start":4,"end":5,"length":2},"window":3,"plateaus":[{"start":2,"end":5,"sum":618}]}"#;
+        assert_eq!(profile.to_json(), expected);
+    }
+
+    const GLITCHY: [i32; 10] = [199, 200, 208, 210, 2000, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_find_glitches_median() {
+        let detector = GlitchDetector::Median {
+            radius: 2,
+            threshold: 100.0,
+        };
+        let expected = vec![Glitch {
+            line: 5,
+            depth: 2000,
+            expected: 209.0,
+        }];
+        assert_eq!(find_glitches(&GLITCHY, detector), expected);
+        assert_eq!(find_glitches(&SAMPLE, detector), vec![]);
+    }
+
+    #[test]
+    fn test_find_glitches_zscore() {
+        let detector = GlitchDetector::ZScore {
+            radius: 3,
+            threshold: 3.0,
+        };
+        let output = find_glitches(&GLITCHY, detector);
+        assert_eq!(output.len(), 1);
+        assert_eq!((output[0].line, output[0].depth), (5, 2000));
+        assert_eq!(find_glitches(&SAMPLE, detector), vec![]);
+
+        let output = find_glitches(&[5, 5, 6, 5, 5], detector);
+        assert_eq!(output.iter().map(|g| g.line).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_repair_glitches() {
+        let glitches = vec![Glitch {
+            line: 5,
+            depth: 2000,
+            expected: 209.0,
+        }];
+
+        let output = repair_glitches(&GLITCHY, &glitches, GlitchRepair::Exclude);
+        assert_eq!(output, vec![199, 200, 208, 210, 207, 240, 269, 260, 263]);
+        assert_eq!(count_increases(&GLITCHY, 1, 1), 7);
+        assert_eq!(count_increases(&output, 1, 1), 6);
+
+        let output = repair_glitches(&GLITCHY, &glitches, GlitchRepair::Interpolate);
+        assert_eq!(
+            output,
+            vec![199, 200, 208, 210, 209, 207, 240, 269, 260, 263]
+        );
+
+        let glitches = vec![
+            Glitch {
+                line: 1,
+                depth: 199,
+                expected: 0.0,
+            },
+            Glitch {
+                line: 10,
+                depth: 263,
+                expected: 0.0,
+            },
+        ];
+        let output = repair_glitches(&SAMPLE, &glitches, GlitchRepair::Interpolate);
+        assert_eq!(
+            output,
+            vec![200, 200, 208, 210, 200, 207, 240, 269, 260, 260]
+        );
+
+        // the span between neighbours doesn't fit in an i32
+        let glitches = vec![Glitch {
+            line: 2,
+            depth: 5,
+            expected: 0.0,
+        }];
+        let output = repair_glitches(
+            &[i32::MIN + 1, 5, i32::MAX],
+            &glitches,
+            GlitchRepair::Interpolate,
+        );
+        assert_eq!(output, vec![i32::MIN + 1, 0, i32::MAX]);
+    }
+
+    #[test]
+    fn test_count_increases_matches_naive() {
+        for window in 1..=5 {
+            for gap in 1..=6 {
+                assert_eq!(
+                    count_increases(&SAMPLE, window, gap),
+                    count_increases_naive(&SAMPLE, window, gap),
+                    "window {window}, gap {gap}"
+                );
+            }
+        }
+
+        let extremes = [i32::MAX, -5, i32::MIN, 7, i32::MAX, i32::MIN];
+        for window in 1..=3 {
+            for gap in 1..=3 {
+                assert_eq!(
+                    count_increases(&extremes, window, gap),
+                    count_increases_naive(&extremes, window, gap),
+                    "extremes, window {window}, gap {gap}"
+                );
+            }
+        }
+    }
+}
@@ -1,60 +1,1165 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
 use std::io::{self, BufRead};
+use std::path::Path;
+use std::str::FromStr;
 
-struct Dive {
-    depth: i32,
-    horizontal: i32,
-    aim: i32,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Forward(i32),
+    Down(i32),
+    Up(i32),
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(num) => write!(f, "forward {num}"),
+            Command::Down(num) => write!(f, "down {num}"),
+            Command::Up(num) => write!(f, "up {num}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CommandError {
+    Malformed(String),
+    UnknownDirection(String),
+    InvalidMagnitude(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Malformed(s) => {
+                write!(f, "expected `<direction> <magnitude>`, got {s:?}")
+            }
+            CommandError::UnknownDirection(s) => write!(f, "unknown direction {s:?}"),
+            CommandError::InvalidMagnitude(s) => write!(f, "invalid magnitude {s:?}"),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cmd, num) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| CommandError::Malformed(s.to_string()))?;
+        let num = num
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| CommandError::InvalidMagnitude(num.to_string()))?;
+        match cmd {
+            "forward" => Ok(Command::Forward(num)),
+            "down" => Ok(Command::Down(num)),
+            "up" => Ok(Command::Up(num)),
+            _ => Err(CommandError::UnknownDirection(cmd.to_string())),
+        }
+    }
+}
+
+// every command next to the line it came from, blank lines are skipped
+fn parse_numbered_commands<I, S>(lines: I) -> Result<Vec<(usize, Command)>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().trim().is_empty())
+        .map(|(i, line)| match line.as_ref().parse::<Command>() {
+            Ok(command) => Ok((i + 1, command)),
+            Err(err) => Err(format!("line {}: {err}", i + 1).into()),
+        })
+        .collect()
+}
+
+// main keeps the line numbers around to report overflows
+#[cfg(test)]
+fn parse_commands<I, S>(lines: I) -> Result<Vec<Command>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let commands = parse_numbered_commands(lines)?;
+    Ok(commands.into_iter().map(|(_, command)| command).collect())
+}
+
+// what a dive is counted in: `i64` with every operation checked, or with the
+// `bigint` feature an integer that can't overflow at all
+trait DiveNum: Clone + fmt::Display {
+    fn zero() -> Self;
+    fn from_i32(num: i32) -> Self;
+    fn try_add(&self, other: &Self) -> Option<Self>;
+    fn try_sub(&self, other: &Self) -> Option<Self>;
+    fn try_mul(&self, other: &Self) -> Option<Self>;
 }
-impl Dive {
+
+impl DiveNum for i64 {
+    fn zero() -> Self {
+        0
+    }
+    fn from_i32(num: i32) -> Self {
+        num as i64
+    }
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+    fn try_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_sub(*other)
+    }
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(*other)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl DiveNum for ibig::IBig {
+    fn zero() -> Self {
+        ibig::IBig::from(0)
+    }
+    fn from_i32(num: i32) -> Self {
+        ibig::IBig::from(num)
+    }
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+    fn try_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Dive<N = i64> {
+    depth: N,
+    horizontal: N,
+    aim: N,
+}
+impl<N: DiveNum> Dive<N> {
     fn new() -> Self {
         Self {
-            depth: 0,
-            horizontal: 0,
-            aim: 0,
+            depth: N::zero(),
+            horizontal: N::zero(),
+            aim: N::zero(),
         }
     }
 }
-fn main() {
-    let directions: Vec<String> = io::stdin()
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Depth,
+    Horizontal,
+    Aim,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Depth => write!(f, "depth"),
+            Field::Horizontal => write!(f, "horizontal"),
+            Field::Aim => write!(f, "aim"),
+        }
+    }
+}
+
+// how a command moves the submarine. On overflow the dive is left untouched
+// and the field that couldn't hold the result is returned.
+trait SubmarineModel {
+    fn apply<N: DiveNum>(&self, dive: &mut Dive<N>, command: Command) -> Result<(), Field>;
+}
+
+// part 1: up and down change the depth directly
+struct Plain;
+
+impl SubmarineModel for Plain {
+    fn apply<N: DiveNum>(&self, dive: &mut Dive<N>, command: Command) -> Result<(), Field> {
+        match command {
+            Command::Forward(num) => {
+                dive.horizontal = dive
+                    .horizontal
+                    .try_add(&N::from_i32(num))
+                    .ok_or(Field::Horizontal)?
+            }
+            Command::Down(num) => {
+                dive.depth = dive.depth.try_add(&N::from_i32(num)).ok_or(Field::Depth)?
+            }
+            Command::Up(num) => {
+                dive.depth = dive.depth.try_sub(&N::from_i32(num)).ok_or(Field::Depth)?
+            }
+        }
+        Ok(())
+    }
+}
+
+// part 2: up and down change the aim, forward dives along it
+struct Aim;
+
+impl SubmarineModel for Aim {
+    fn apply<N: DiveNum>(&self, dive: &mut Dive<N>, command: Command) -> Result<(), Field> {
+        match command {
+            Command::Forward(num) => {
+                let num = N::from_i32(num);
+                let horizontal = dive.horizontal.try_add(&num).ok_or(Field::Horizontal)?;
+                let depth = dive
+                    .aim
+                    .try_mul(&num)
+                    .and_then(|change| dive.depth.try_add(&change))
+                    .ok_or(Field::Depth)?;
+                dive.horizontal = horizontal;
+                dive.depth = depth;
+            }
+            Command::Down(num) => {
+                dive.aim = dive.aim.try_add(&N::from_i32(num)).ok_or(Field::Aim)?
+            }
+            Command::Up(num) => dive.aim = dive.aim.try_sub(&N::from_i32(num)).ok_or(Field::Aim)?,
+        }
+        Ok(())
+    }
+}
+
+// the command, counted from 0, that would have taken a field past its type
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Overflow {
+    step: usize,
+    command: Command,
+    field: Field,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` overflows {}", self.command, self.field)
+    }
+}
+
+impl Error for Overflow {}
+
+// names the input line of an overflow, given the commands it was run on
+fn at_line(commands: &[(usize, Command)]) -> impl Fn(Overflow) -> Box<dyn Error> + '_ {
+    |overflow| format!("line {}: {overflow}", commands[overflow.step].0).into()
+}
+
+// shortest list of commands taking a model from the surface to a position,
+// `None` when the model can't get there. Magnitudes are always positive.
+trait Planner {
+    fn plan(&self, horizontal: i32, depth: i32) -> Option<Vec<Command>>;
+}
+
+// one command, except for i32::MIN whose magnitude doesn't fit in one up
+fn vertical(num: i32) -> Vec<Command> {
+    if num > 0 {
+        return vec![Command::Down(num)];
+    }
+    match num.checked_neg() {
+        Some(up) => vec![Command::Up(up)],
+        None => vec![Command::Up(i32::MAX), Command::Up(1)],
+    }
+}
+
+// every command moves one axis, so one command per axis that isn't zero
+impl Planner for Plain {
+    fn plan(&self, horizontal: i32, depth: i32) -> Option<Vec<Command>> {
+        if horizontal < 0 {
+            return None;
+        }
+        let mut commands = Vec::new();
+        if horizontal > 0 {
+            commands.push(Command::Forward(horizontal));
+        }
+        if depth != 0 {
+            commands.extend(vertical(depth));
+        }
+        Some(commands)
+    }
+}
+
+// the largest divisor of `num` below `limit`. Divisors come in pairs
+// `d, num / d` with `d <= √num`, so the search is bounded by the number rather
+// than by the limit.
+fn largest_divisor_below(num: u32, limit: u32) -> u32 {
+    let mut largest = 1;
+    let mut d = 1;
+    while d <= num / d {
+        if num.is_multiple_of(d) {
+            for divisor in [d, num / d] {
+                if divisor < limit {
+                    largest = largest.max(divisor);
+                }
+            }
+        }
+        d += 1;
+    }
+    largest
+}
+
+// depth only changes while moving forward, by `aim * forward`:
+// - no depth is a single forward
+// - a depth that `horizontal` divides is one aim change plus one forward, two
+//   aim changes when that aim is i32::MIN
+// - otherwise move forward at aim 0 first, then cover the depth with a shorter
+//   forward whose length divides it. Two commands can't do it, so three is
+//   the shortest.
+impl Planner for Aim {
+    fn plan(&self, horizontal: i32, depth: i32) -> Option<Vec<Command>> {
+        if horizontal < 0 || (horizontal == 0 && depth != 0) {
+            return None;
+        }
+        if depth == 0 {
+            return Plain.plan(horizontal, 0);
+        }
+        if depth % horizontal == 0 {
+            let mut commands = vertical(depth / horizontal);
+            commands.push(Command::Forward(horizontal));
+            return Some(commands);
+        }
+        let last = largest_divisor_below(depth.unsigned_abs(), horizontal as u32) as i32;
+        let mut commands = vec![Command::Forward(horizontal - last)];
+        commands.extend(vertical(depth / last));
+        commands.push(Command::Forward(last));
+        Some(commands)
+    }
+}
+
+// plans the commands and replays them through the model to make sure they land
+// on the target
+fn plan_verified<M: SubmarineModel + Planner>(
+    model: &M,
+    horizontal: i32,
+    depth: i32,
+) -> Result<Vec<Command>, Box<dyn Error>> {
+    let commands = model.plan(horizontal, depth).ok_or(format!(
+        "horizontal {horizontal}, depth {depth} can't be reached"
+    ))?;
+    let dive: Dive = run(model, &commands)?;
+    if (dive.horizontal, dive.depth) != (horizontal as i64, depth as i64) {
+        return Err(format!(
+            "plan ends at horizontal {}, depth {} instead of {horizontal}, {depth}",
+            dive.horizontal, dive.depth
+        )
+        .into());
+    }
+    Ok(commands)
+}
+
+// runs every command, showing `observe` the state right after each of them
+fn run_with<N: DiveNum>(
+    model: &impl SubmarineModel,
+    commands: &[Command],
+    mut observe: impl FnMut(&Command, &Dive<N>),
+) -> Result<Dive<N>, Overflow> {
+    let mut dive = Dive::new();
+    for (step, command) in commands.iter().enumerate() {
+        model.apply(&mut dive, *command).map_err(|field| Overflow {
+            step,
+            command: *command,
+            field,
+        })?;
+        observe(command, &dive);
+    }
+    Ok(dive)
+}
+
+fn run<N: DiveNum>(model: &impl SubmarineModel, commands: &[Command]) -> Result<Dive<N>, Overflow> {
+    run_with(model, commands, |_, _| {})
+}
+
+// the state before any command, followed by the state after each of them
+#[derive(Debug, PartialEq)]
+struct Trajectory {
+    steps: Vec<(Option<Command>, Dive)>,
+}
+
+impl Trajectory {
+    fn record(model: &impl SubmarineModel, commands: &[Command]) -> Result<Self, Overflow> {
+        let mut steps = vec![(None, Dive::new())];
+        run_with(model, commands, |command, dive| {
+            steps.push((Some(*command), *dive))
+        })?;
+        Ok(Self { steps })
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("step,command,horizontal,depth,aim\n");
+        for (step, (command, dive)) in self.steps.iter().enumerate() {
+            let command = command.map(|c| c.to_string()).unwrap_or_default();
+            writeln!(
+                out,
+                "{step},{command},{},{},{}",
+                dive.horizontal, dive.depth, dive.aim
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 400.0;
+const SVG_MARGIN: f64 = 40.0;
+const SVG_COLORS: [&str; 4] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd"];
+
+// depth (downwards) against horizontal distance, one line per trajectory, all
+// on the same scale so they can be compared
+fn trajectories_to_svg(trajectories: &[(&str, &Trajectory)]) -> String {
+    let all_dives = || {
+        trajectories
+            .iter()
+            .flat_map(|(_, t)| t.steps.iter().map(|s| s.1))
+    };
+    let max_horizontal = all_dives().map(|d| d.horizontal).max().unwrap_or(0);
+    let min_depth = all_dives().map(|d| d.depth).min().unwrap_or(0).min(0);
+    let max_depth = all_dives().map(|d| d.depth).max().unwrap_or(0);
+    let scale_x = (SVG_WIDTH - 2.0 * SVG_MARGIN) / max_horizontal.max(1) as f64;
+    // the depth span can be wider than an i64
+    let scale_y = (SVG_HEIGHT - 2.0 * SVG_MARGIN) / (max_depth as f64 - min_depth as f64).max(1.0);
+    let x = |dive: &Dive| SVG_MARGIN + dive.horizontal as f64 * scale_x;
+    let y = |dive: &Dive| SVG_MARGIN + (dive.depth as f64 - min_depth as f64) * scale_y;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SVG_WIDTH}" height="{SVG_HEIGHT}" viewBox="0 0 {SVG_WIDTH} {SVG_HEIGHT}">"#
+    )
+    .unwrap();
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(
+        out,
+        r##"<text x="{SVG_MARGIN}" y="{}" font-size="12">horizontal 0..{max_horizontal}, depth {min_depth}..{max_depth}</text>"##,
+        SVG_MARGIN / 2.0
+    )
+    .unwrap();
+    for (i, (name, trajectory)) in trajectories.iter().enumerate() {
+        let color = SVG_COLORS[i % SVG_COLORS.len()];
+        let points: Vec<String> = trajectory
+            .steps
+            .iter()
+            .map(|(_, dive)| format!("{:.1},{:.1}", x(dive), y(dive)))
+            .collect();
+        writeln!(
+            out,
+            r#"<polyline fill="none" stroke="{color}" stroke-width="1.5" points="{}"/>"#,
+            points.join(" ")
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}" font-size="12" fill="{color}">{name}</text>"#,
+            SVG_WIDTH - SVG_MARGIN - 100.0,
+            SVG_MARGIN + 15.0 * i as f64
+        )
+        .unwrap();
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn export_trajectories(dir: &Path, numbered: &[(usize, Command)]) -> Result<(), Box<dyn Error>> {
+    let commands: Vec<Command> = numbered.iter().map(|(_, command)| *command).collect();
+    let plain = Trajectory::record(&Plain, &commands).map_err(at_line(numbered))?;
+    let aim = Trajectory::record(&Aim, &commands).map_err(at_line(numbered))?;
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("trajectory_plain.csv"), plain.to_csv())?;
+    fs::write(dir.join("trajectory_aim.csv"), aim.to_csv())?;
+    fs::write(
+        dir.join("trajectory.svg"),
+        trajectories_to_svg(&[("plain", &plain), ("aim", &aim)]),
+    )?;
+    println!("trajectories written to {}", dir.display());
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+// a breakpoint like `depth > 1000` or `aim < 0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Condition {
+    field: Field,
+    op: Op,
+    value: i64,
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [field, op, value] = parts.as_slice() else {
+            return Err(format!("expected `<field> <op> <value>`, got {s:?}"));
+        };
+        let field = match *field {
+            "depth" => Field::Depth,
+            "horizontal" => Field::Horizontal,
+            "aim" => Field::Aim,
+            _ => return Err(format!("unknown field {field:?}")),
+        };
+        let op = match *op {
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            "==" => Op::Eq,
+            "!=" => Op::Ne,
+            _ => return Err(format!("unknown operator {op:?}")),
+        };
+        let value = value
+            .parse::<i64>()
+            .map_err(|_| format!("invalid value {value:?}"))?;
+        Ok(Condition { field, op, value })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Eq => "==",
+            Op::Ne => "!=",
+        };
+        write!(f, "{} {op} {}", self.field, self.value)
+    }
+}
+
+impl Condition {
+    fn holds(&self, dive: &Dive) -> bool {
+        let current = match self.field {
+            Field::Depth => dive.depth,
+            Field::Horizontal => dive.horizontal,
+            Field::Aim => dive.aim,
+        };
+        match self.op {
+            Op::Gt => current > self.value,
+            Op::Ge => current >= self.value,
+            Op::Lt => current < self.value,
+            Op::Le => current <= self.value,
+            Op::Eq => current == self.value,
+            Op::Ne => current != self.value,
+        }
+    }
+}
+
+// steps through the commands one at a time. `position` is how many commands
+// have been applied to `dive` so far.
+struct Debugger<'a, M: SubmarineModel> {
+    model: &'a M,
+    commands: &'a [(usize, Command)],
+    position: usize,
+    dive: Dive,
+    breakpoints: Vec<Condition>,
+}
+
+impl<'a, M: SubmarineModel> Debugger<'a, M> {
+    fn new(model: &'a M, commands: &'a [(usize, Command)]) -> Self {
+        Self {
+            model,
+            commands,
+            position: 0,
+            dive: Dive::new(),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    // `Ok(None)` at the end of the commands. An overflow leaves the debugger on
+    // the command before the one that failed.
+    fn step(&mut self) -> Result<Option<(usize, Command)>, Overflow> {
+        let Some((line, command)) = self.commands.get(self.position).copied() else {
+            return Ok(None);
+        };
+        self.model
+            .apply(&mut self.dive, command)
+            .map_err(|field| Overflow {
+                step: self.position,
+                command,
+                field,
+            })?;
+        self.position += 1;
+        Ok(Some((line, command)))
+    }
+
+    // runs until a breakpoint goes from not holding to holding, so a
+    // breakpoint that stays true doesn't stop every single step
+    fn resume(&mut self) -> Result<Option<Condition>, Overflow> {
+        loop {
+            let before: Vec<bool> = self
+                .breakpoints
+                .iter()
+                .map(|b| b.holds(&self.dive))
+                .collect();
+            if self.step()?.is_none() {
+                return Ok(None);
+            }
+            let hit = self
+                .breakpoints
+                .iter()
+                .zip(before)
+                .find(|(b, held)| !held && b.holds(&self.dive));
+            if let Some((breakpoint, _)) = hit {
+                return Ok(Some(*breakpoint));
+            }
+        }
+    }
+
+    // leaves the state right after the command on `line`, or the last one
+    // before it. Jumping backwards replays from the surface, breakpoints are
+    // ignored.
+    fn jump(&mut self, line: usize) -> Result<(), Overflow> {
+        let target = self.commands.partition_point(|(l, _)| *l <= line);
+        if target < self.position {
+            self.position = 0;
+            self.dive = Dive::new();
+        }
+        while self.position < target {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> String {
+        let at = match self.position.checked_sub(1) {
+            Some(i) => format!("line {}: {}", self.commands[i].0, self.commands[i].1),
+            None => "start".to_string(),
+        };
+        format!(
+            "{at} -> horizontal {}, depth {}, aim {}",
+            self.dive.horizontal, self.dive.depth, self.dive.aim
+        )
+    }
+}
+
+const DEBUGGER_HELP: &str = "commands:
+  s, step [n]        run the next n commands (1 by default)
+  c, continue        run until a breakpoint starts holding or the end
+  b, break <cond>    add a breakpoint, e.g. `break depth > 1000`
+  d, delete <n>      remove breakpoint n
+  l, list            list breakpoints
+  j, jump <line>     go to the state right after that line
+  p, print           show the current state
+  q, quit";
+
+fn debug<M: SubmarineModel>(
+    debugger: &mut Debugger<M>,
+    input: impl BufRead,
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    let commands = debugger.commands;
+    writeln!(output, "{}", debugger.status())?;
+    for line in input.lines() {
+        let line = line?;
+        let (cmd, arg) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let arg = arg.trim();
+        match cmd {
+            "" => continue,
+            "s" | "step" => {
+                let times = arg.parse::<usize>().unwrap_or(1);
+                for _ in 0..times {
+                    match debugger.step() {
+                        Ok(Some(_)) => writeln!(output, "{}", debugger.status())?,
+                        Ok(None) => {
+                            writeln!(output, "end of commands")?;
+                            break;
+                        }
+                        Err(overflow) => {
+                            writeln!(output, "{}", at_line(commands)(overflow))?;
+                            break;
+                        }
+                    }
+                }
+            }
+            "c" | "continue" => match debugger.resume() {
+                Ok(Some(breakpoint)) => writeln!(
+                    output,
+                    "breakpoint `{breakpoint}` hit\n{}",
+                    debugger.status()
+                )?,
+                Ok(None) => writeln!(output, "end of commands\n{}", debugger.status())?,
+                Err(overflow) => writeln!(
+                    output,
+                    "{}\n{}",
+                    at_line(commands)(overflow),
+                    debugger.status()
+                )?,
+            },
+            "b" | "break" => match arg.parse::<Condition>() {
+                Ok(condition) => {
+                    debugger.breakpoints.push(condition);
+                    writeln!(
+                        output,
+                        "breakpoint {}: {condition}",
+                        debugger.breakpoints.len()
+                    )?;
+                }
+                Err(err) => writeln!(output, "{err}")?,
+            },
+            "d" | "delete" => match arg.parse::<usize>() {
+                Ok(n) if (1..=debugger.breakpoints.len()).contains(&n) => {
+                    let removed = debugger.breakpoints.remove(n - 1);
+                    writeln!(output, "deleted breakpoint {n}: {removed}")?;
+                }
+                _ => writeln!(output, "no breakpoint {arg:?}")?,
+            },
+            "l" | "list" => {
+                for (i, breakpoint) in debugger.breakpoints.iter().enumerate() {
+                    writeln!(output, "breakpoint {}: {breakpoint}", i + 1)?;
+                }
+            }
+            "j" | "jump" => match arg.parse::<usize>() {
+                Ok(line) => {
+                    if let Err(overflow) = debugger.jump(line) {
+                        writeln!(output, "{}", at_line(commands)(overflow))?;
+                    }
+                    writeln!(output, "{}", debugger.status())?;
+                }
+                Err(_) => writeln!(output, "invalid line {arg:?}")?,
+            },
+            "p" | "print" => writeln!(output, "{}", debugger.status())?,
+            "q" | "quit" => break,
+            _ => writeln!(output, "unknown command {cmd:?}\n{DEBUGGER_HELP}")?,
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("plan") {
+        let (Some(horizontal), Some(depth)) = (args.get(1), args.get(2)) else {
+            return Err("usage: plan <horizontal> <depth> [--aim]".into());
+        };
+        let (horizontal, depth) = (horizontal.parse::<i32>()?, depth.parse::<i32>()?);
+        let commands = match args.iter().any(|arg| arg == "--aim") {
+            true => plan_verified(&Aim, horizontal, depth)?,
+            false => plan_verified(&Plain, horizontal, depth)?,
+        };
+        commands.iter().for_each(|command| println!("{command}"));
+        return Ok(());
+    }
+
+    // the commands come from a file, stdin drives the debugger
+    if args.first().map(String::as_str) == Some("debug") {
+        let path = args.get(1).ok_or("usage: debug <commands file> [--aim]")?;
+        let contents = fs::read_to_string(path)?;
+        let commands = parse_numbered_commands(contents.lines())?;
+        println!("{DEBUGGER_HELP}");
+        match args.iter().any(|arg| arg == "--aim") {
+            true => debug(
+                &mut Debugger::new(&Aim, &commands),
+                io::stdin().lock(),
+                &mut io::stdout(),
+            )?,
+            false => debug(
+                &mut Debugger::new(&Plain, &commands),
+                io::stdin().lock(),
+                &mut io::stdout(),
+            )?,
+        }
+        return Ok(());
+    }
+
+    let lines = io::stdin()
         .lock()
         .lines()
-        .flatten()
-        .collect();
-    let mut dive = Dive::new();
+        .collect::<Result<Vec<String>, _>>()?;
+    let numbered = parse_numbered_commands(lines.iter().map(String::as_str))?;
+
+    if args.first().map(String::as_str) == Some("trajectory") {
+        let dir = args.get(1).map(String::as_str).unwrap_or(".");
+        return export_trajectories(Path::new(dir), &numbered);
+    }
+
+    if args.iter().any(|arg| arg == "--bigint") {
+        #[cfg(feature = "bigint")]
+        return print_parts::<ibig::IBig>(&numbered);
+        #[cfg(not(feature = "bigint"))]
+        return Err("--bigint needs a build with `--features bigint`".into());
+    }
+    print_parts::<i64>(&numbered)
+}
+
+fn print_parts<N: DiveNum>(numbered: &[(usize, Command)]) -> Result<(), Box<dyn Error>> {
+    let commands: Vec<Command> = numbered.iter().map(|(_, command)| *command).collect();
 
     // Part 1
-    directions.iter().for_each(|direction| {
-        let pair: Vec<&str> = direction.split(' ').collect();
-        let cmd = pair.first().unwrap();
-        let num = pair.last().unwrap().parse::<i32>().unwrap();
-        match *cmd {
-            "forward" => dive.horizontal += num,
-            "down" => dive.depth += num,
-            "up" => dive.depth -= num,
-            _ => unreachable!("not this"),
+    let dive: Dive<N> = run(&Plain, &commands).map_err(at_line(numbered))?;
+    let horizontal_depth = dive
+        .depth
+        .try_mul(&dive.horizontal)
+        .ok_or("part 1: horizontal position by depth overflows")?;
+    println!(
+        "final horizontal position by your final depth is: {}",
+        horizontal_depth
+    );
+
+    // Part 2
+    let dive: Dive<N> = run(&Aim, &commands).map_err(at_line(numbered))?;
+    let horizontal_depth = dive
+        .depth
+        .try_mul(&dive.horizontal)
+        .ok_or("part 2: horizontal position by depth overflows")?;
+    println!(
+        "[WITH AIM] final horizontal position by your final depth is: {}",
+        horizontal_depth
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!("forward 5".parse(), Ok(Command::Forward(5)));
+        assert_eq!("down 12".parse(), Ok(Command::Down(12)));
+        assert_eq!("up 3".parse(), Ok(Command::Up(3)));
+
+        assert_eq!(
+            "sideways 3".parse::<Command>(),
+            Err(CommandError::UnknownDirection("sideways".to_string()))
+        );
+        assert_eq!(
+            "up three".parse::<Command>(),
+            Err(CommandError::InvalidMagnitude("three".to_string()))
+        );
+        assert_eq!(
+            "forward".parse::<Command>(),
+            Err(CommandError::Malformed("forward".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let output = parse_commands(SAMPLE.lines()).unwrap();
+        assert_eq!(output.len(), 6);
+        assert_eq!(output[3], Command::Up(3));
+
+        let output = parse_commands("forward 5\n\nback 2".lines());
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "line 3: unknown direction \"back\""
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let commands = parse_commands(SAMPLE.lines()).unwrap();
+
+        let dive: Dive = run(&Plain, &commands).unwrap();
+        assert_eq!((dive.horizontal, dive.depth), (15, 10));
+        assert_eq!(dive.horizontal * dive.depth, 150);
+
+        let dive: Dive = run(&Aim, &commands).unwrap();
+        assert_eq!((dive.horizontal, dive.depth, dive.aim), (15, 60, 10));
+        assert_eq!(dive.horizontal * dive.depth, 900);
+    }
+
+    #[test]
+    fn test_run_overflow() {
+        // aim climbs by a million per line and forward dives along it, so depth
+        // grows quadratically and leaves i64 long before the input ends
+        let mut input = String::new();
+        for _ in 0..200_000 {
+            input.push_str("down 1000000\nforward 1000000\n");
         }
-    });
+        let numbered = parse_numbered_commands(input.lines()).unwrap();
+        let commands: Vec<Command> = numbered.iter().map(|(_, command)| *command).collect();
 
-    let horizontal_depth = dive.depth * dive.horizontal;
-    println!("final horizontal position by your final depth is: {}", horizontal_depth);
+        let overflow = run::<i64>(&Aim, &commands).unwrap_err();
+        assert_eq!(overflow.command, Command::Forward(1_000_000));
+        assert_eq!(overflow.field, Field::Depth);
+        // after k pairs depth is 10^12 * k * (k + 1) / 2
+        let k = (1..).find(|k: &i64| (k * (k + 1) / 2).checked_mul(1_000_000_000_000).is_none());
+        assert_eq!(overflow.step as i64, 2 * k.unwrap() - 1);
+        assert_eq!(
+            at_line(&numbered)(overflow).to_string(),
+            format!(
+                "line {}: `forward 1000000` overflows depth",
+                overflow.step + 1
+            )
+        );
 
-    let mut dive = Dive::new();
-    // Part 2
-    directions.iter().for_each(|direction| {
-        let pair: Vec<&str> = direction.split(' ').collect();
-        let cmd = pair.first().unwrap();
-        let num = pair.last().unwrap().parse::<i32>().unwrap();
-        match *cmd {
-            "forward" => {
-                dive.horizontal += num;
-                dive.depth += dive.aim * num;
+        // plain never gets anywhere near
+        let dive: Dive = run(&Plain, &commands).unwrap();
+        assert_eq!(
+            (dive.horizontal, dive.depth),
+            (200_000_000_000, 200_000_000_000)
+        );
 
-            },
-            "down" => dive.aim += num,
-            "up" => dive.aim -= num,
-            _ => unreachable!("not this"),
+        let dive = Dive {
+            aim: i64::MAX,
+            ..Dive::new()
+        };
+        let mut after = dive;
+        assert_eq!(Aim.apply(&mut after, Command::Down(1)), Err(Field::Aim));
+        assert_eq!(after, dive);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_run_bigint() {
+        let commands = parse_commands(SAMPLE.lines()).unwrap();
+        let dive: Dive<ibig::IBig> = run(&Aim, &commands).unwrap();
+        assert_eq!(dive.depth.to_string(), "60");
+
+        let commands = [Command::Down(i32::MAX), Command::Forward(i32::MAX)].repeat(100_000);
+        let dive: Dive<ibig::IBig> = run(&Aim, &commands).unwrap();
+        // sum of k * i32::MAX^2 for k in 1..=100_000
+        let max = ibig::IBig::from(i32::MAX);
+        let expected = &max * &max * ibig::IBig::from(100_000u64 * 100_001 / 2);
+        assert_eq!(dive.depth, expected);
+    }
+
+    #[test]
+    fn test_plan_plain() {
+        assert_eq!(Plain.plan(0, 0), Some(vec![]));
+        assert_eq!(
+            Plain.plan(15, 10),
+            Some(vec![Command::Forward(15), Command::Down(10)])
+        );
+        assert_eq!(Plain.plan(0, -3), Some(vec![Command::Up(3)]));
+        assert_eq!(Plain.plan(-1, 3), None);
+    }
+
+    #[test]
+    fn test_plan_aim() {
+        assert_eq!(Aim.plan(0, 0), Some(vec![]));
+        assert_eq!(Aim.plan(7, 0), Some(vec![Command::Forward(7)]));
+        assert_eq!(
+            Aim.plan(15, 60),
+            Some(vec![Command::Down(4), Command::Forward(15)])
+        );
+        assert_eq!(
+            Aim.plan(15, 62),
+            Some(vec![
+                Command::Forward(13),
+                Command::Down(31),
+                Command::Forward(2)
+            ])
+        );
+        assert_eq!(
+            Aim.plan(4, -7),
+            Some(vec![
+                Command::Forward(3),
+                Command::Up(7),
+                Command::Forward(1)
+            ])
+        );
+        assert_eq!(Aim.plan(0, 5), None);
+        assert_eq!(Aim.plan(-2, 0), None);
+
+        // the divisor search doesn't walk the whole distance
+        assert_eq!(
+            Aim.plan(i32::MAX, 3),
+            Some(vec![
+                Command::Forward(i32::MAX - 3),
+                Command::Down(1),
+                Command::Forward(3)
+            ])
+        );
+        assert_eq!(
+            Aim.plan(i32::MAX, i32::MAX - 1),
+            Some(vec![
+                Command::Forward(1),
+                Command::Down(1),
+                Command::Forward(i32::MAX - 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_plan_verified() {
+        for horizontal in 0..=12 {
+            for depth in -40..=40 {
+                let plain = plan_verified(&Plain, horizontal, depth).unwrap();
+                assert!(plain.len() <= 2);
+                match plan_verified(&Aim, horizontal, depth) {
+                    Ok(aim) => assert!(aim.len() <= 3),
+                    Err(_) => assert!(horizontal == 0 && depth != 0),
+                }
+            }
         }
-    });
-    let horizontal_depth = dive.depth * dive.horizontal;
-    println!("[WITH AIM] final horizontal position by your final depth is: {}", horizontal_depth);
+        assert_eq!(
+            plan_verified(&Aim, 0, 5).unwrap_err().to_string(),
+            "horizontal 0, depth 5 can't be reached"
+        );
+
+        // no up command covers i32::MIN in one go, it takes two
+        assert_eq!(
+            plan_verified(&Plain, 0, i32::MIN).unwrap(),
+            vec![Command::Up(i32::MAX), Command::Up(1)]
+        );
+        assert_eq!(
+            plan_verified(&Aim, 1, i32::MIN).unwrap(),
+            vec![Command::Up(i32::MAX), Command::Up(1), Command::Forward(1)]
+        );
+        assert_eq!(
+            plan_verified(&Aim, 2, i32::MIN).unwrap(),
+            vec![Command::Up(1 << 30), Command::Forward(2)]
+        );
+        assert!(plan_verified(&Plain, i32::MAX, i32::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let expected = Condition {
+            field: Field::Depth,
+            op: Op::Gt,
+            value: 1000,
+        };
+        assert_eq!("depth > 1000".parse(), Ok(expected));
+        assert_eq!(expected.to_string(), "depth > 1000");
+
+        let expected = Condition {
+            field: Field::Aim,
+            op: Op::Lt,
+            value: 0,
+        };
+        assert_eq!(" aim  <  0 ".parse(), Ok(expected));
+
+        assert!("depth >".parse::<Condition>().is_err());
+        assert!("speed > 3".parse::<Condition>().is_err());
+        assert!("depth => 3".parse::<Condition>().is_err());
+        assert!("depth > x".parse::<Condition>().is_err());
+    }
+
+    #[test]
+    fn test_debugger() {
+        let commands = parse_numbered_commands(SAMPLE.lines()).unwrap();
+        let mut debugger = Debugger::new(&Aim, &commands);
+        debugger.breakpoints.push("depth > 30".parse().unwrap());
+
+        assert_eq!(debugger.step(), Ok(Some((1, Command::Forward(5)))));
+        assert_eq!(debugger.resume(), Ok(Some(debugger.breakpoints[0])));
+        assert_eq!(
+            debugger.status(),
+            "line 3: forward 8 -> horizontal 13, depth 40, aim 5"
+        );
+        // still true, so it doesn't stop again
+        assert_eq!(debugger.resume(), Ok(None));
+        assert_eq!(debugger.position, 6);
+
+        debugger.jump(2).unwrap();
+        assert_eq!(
+            debugger.status(),
+            "line 2: down 5 -> horizontal 5, depth 0, aim 5"
+        );
+        debugger.jump(0).unwrap();
+        assert_eq!(debugger.status(), "start -> horizontal 0, depth 0, aim 0");
+        debugger.jump(100).unwrap();
+        assert_eq!(
+            Ok(debugger.dive),
+            run(&Aim, &parse_commands(SAMPLE.lines()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_debug_session() {
+        let commands =
+            parse_numbered_commands("forward 5\n\ndown 5\nforward 8\nup 9".lines()).unwrap();
+        let mut debugger = Debugger::new(&Aim, &commands);
+        let input = "step 2\nbreak aim < 0\nbreak aim <\nlist\ncontinue\njump 3\ndelete 1\nc\nstep\nq\nstep";
+        let mut output = Vec::new();
+        debug(&mut debugger, input.as_bytes(), &mut output).unwrap();
+        let expected = "start -> horizontal 0, depth 0, aim 0
+line 1: forward 5 -> horizontal 5, depth 0, aim 0
+line 3: down 5 -> horizontal 5, depth 0, aim 5
+breakpoint 1: aim < 0
+expected `<field> <op> <value>`, got \"aim <\"
+breakpoint 1: aim < 0
+breakpoint `aim < 0` hit
+line 5: up 9 -> horizontal 13, depth 40, aim -4
+line 3: down 5 -> horizontal 5, depth 0, aim 5
+deleted breakpoint 1: aim < 0
+end of commands
+line 5: up 9 -> horizontal 13, depth 40, aim -4
+end of commands
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_trajectory_record() {
+        let commands = parse_commands(SAMPLE.lines()).unwrap();
+        let trajectory = Trajectory::record(&Aim, &commands).unwrap();
+        assert_eq!(trajectory.steps.len(), 7);
+        assert_eq!(trajectory.steps[0], (None, Dive::new()));
+        assert_eq!(
+            trajectory.steps[3],
+            (
+                Some(Command::Forward(8)),
+                Dive {
+                    depth: 40,
+                    horizontal: 13,
+                    aim: 5
+                }
+            )
+        );
+        assert_eq!(Ok(trajectory.steps.last().unwrap().1), run(&Aim, &commands));
+    }
+
+    #[test]
+    fn test_trajectory_to_csv() {
+        let commands = parse_commands("forward 5\ndown 5\nforward 8".lines()).unwrap();
+        let expected = "step,command,horizontal,depth,aim
+0,,0,0,0
+1,forward 5,5,0,0
+2,down 5,5,0,5
+3,forward 8,13,40,5
+";
+        assert_eq!(
+            Trajectory::record(&Aim, &commands).unwrap().to_csv(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_trajectories_to_svg() {
+        let commands = parse_commands(SAMPLE.lines()).unwrap();
+        let plain = Trajectory::record(&Plain, &commands).unwrap();
+        let aim = Trajectory::record(&Aim, &commands).unwrap();
+        let output = trajectories_to_svg(&[("plain", &plain), ("aim", &aim)]);
+        assert!(output.starts_with("<svg "));
+        assert!(output.ends_with("</svg>\n"));
+        assert_eq!(output.matches("<polyline ").count(), 2);
+        assert!(output.contains("horizontal 0..15, depth 0..60"));
+        // both start at the surface, the top left corner of the plot
+        assert_eq!(output.matches(r#"points="40.0,40.0 "#).count(), 2);
+        // the aim line ends at the bottom right corner
+        assert!(output.contains(r#" 760.0,360.0"/>"#));
+
+        // depths from near i64::MIN to near i64::MAX
+        let mut commands = vec![Command::Up(i32::MAX); 1000];
+        commands.push(Command::Forward(2_330_000));
+        commands.extend(vec![Command::Down(i32::MAX); 2000]);
+        commands.extend(vec![Command::Forward(2_215_000); 2]);
+        let aim = Trajectory::record(&Aim, &commands).unwrap();
+        let output = trajectories_to_svg(&[("aim", &aim)]);
+        assert!(output.contains(r#" 760.0,360.0"/>"#));
+    }
 }
@@ -1,57 +1,1052 @@
-use std::fs;
+use std::{collections::HashMap, env, error::Error, fs, ops::Range, str::FromStr, time::Instant};
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take, take_while1},
-    combinator::{peek, value},
+    bytes::complete::{tag, tag_no_case, take, take_while1},
+    character::complete::{digit1, space1},
+    combinator::{map, opt, peek, value},
+    error::ErrorKind,
     multi::many0,
+    sequence::{pair, preceded, tuple},
     IResult,
 };
 
-fn parse_str_num(i: &str) -> IResult<&str, &str> {
-    let (tail, out) = peek(alt((
-        value("1", tag("one")),
-        value("2", tag("two")),
-        value("3", tag("three")),
-        value("4", tag("four")),
-        value("5", tag("five")),
-        value("6", tag("six")),
-        value("7", tag("seven")),
-        value("8", tag("eight")),
-        value("9", tag("nine")),
-    )))(i)?;
-    let (tail, _) = take(1usize)(tail)?;
-    Ok((tail, out))
-}
-fn parse_num(i: &str) -> IResult<&str, Vec<&str>> {
-    many0(alt((
-        take_while1(move |c: char| c.is_numeric()),
-        parse_str_num,
-        value("", take(1usize)),
-    )))(i)
-}
-
-fn parser(i: &str) -> i32 {
-    println!("{}", i);
-    let (_, num) = parse_num(i).expect("numer not returned");
+const ENGLISH: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const SPANISH: [&str; 9] = [
+    "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+const GERMAN: [&str; 9] = [
+    "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+
+// spelled out digits and the digit each one stands for
+#[derive(Debug, Clone, PartialEq)]
+struct Vocabulary {
+    words: Vec<(String, String)>,
+    ignore_case: bool,
+}
+
+impl Vocabulary {
+    // words[0] is one, words[8] is nine
+    fn from_words(words: &[&str; 9]) -> Self {
+        Self {
+            words: words
+                .iter()
+                .zip(1..)
+                .map(|(word, digit)| (word.to_string(), digit.to_string()))
+                .collect(),
+            ignore_case: false,
+        }
+    }
+
+    fn builtin(language: &str) -> Option<Self> {
+        match language {
+            "en" | "english" => Some(Self::from_words(&ENGLISH)),
+            "es" | "spanish" => Some(Self::from_words(&SPANISH)),
+            "de" | "german" => Some(Self::from_words(&GERMAN)),
+            _ => None,
+        }
+    }
+
+    // one `<word> <digit>` per line, blank lines and `#` comments are skipped
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut words = Vec::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [word, digit] if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) => {
+                    words.push((word.to_string(), digit.to_string()))
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `<word> <digit>`, got {line:?}",
+                        i + 1
+                    ))
+                }
+            }
+        }
+        match words.is_empty() {
+            true => Err("no words in vocabulary".to_string()),
+            false => Ok(Self {
+                words,
+                ignore_case: false,
+            }),
+        }
+    }
+}
+
+// only peeks at the word and consumes a single char, so overlapping words
+// like "eightwo" still both match
+fn parse_str_num<'a>(i: &'a str, vocabulary: &'a Vocabulary) -> IResult<&'a str, &'a str> {
+    for (word, digit) in &vocabulary.words {
+        let matched: IResult<&str, &str> = match vocabulary.ignore_case {
+            true => peek(tag_no_case(word.as_str()))(i),
+            false => peek(tag(word.as_str()))(i),
+        };
+        if matched.is_ok() {
+            let (tail, _) = take(1usize)(i)?;
+            return Ok((tail, digit.as_str()));
+        }
+    }
+    Err(nom::Err::Error(nom::error::Error::new(i, ErrorKind::Tag)))
+}
+// part 1 only counts digits, part 2 also spelled out ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Digits,
+    DigitsAndWords,
+}
+
+fn parse_num<'a>(
+    i: &'a str,
+    mode: Mode,
+    vocabulary: &'a Vocabulary,
+) -> IResult<&'a str, Vec<&'a str>> {
+    match mode {
+        Mode::Digits => many0(alt((
+            take_while1(move |c: char| c.is_ascii_digit()),
+            value("", take(1usize)),
+        )))(i),
+        Mode::DigitsAndWords => many0(alt((
+            take_while1(move |c: char| c.is_ascii_digit()),
+            |i| parse_str_num(i, vocabulary),
+            value("", take(1usize)),
+        )))(i),
+    }
+}
+
+// tokenises the whole line, `None` when it has no digit at all
+fn parser(i: &str, mode: Mode, vocabulary: &Vocabulary) -> Option<i32> {
+    let (_, num) = parse_num(i, mode, vocabulary).expect("numer not returned");
     let binding = num.join("");
     let mut chars = binding.trim().chars();
-    println!("{:?}", chars);
-    let first = chars.next().unwrap_or('0');
-    let last = chars.next_back().unwrap_or(first);
-    let val = format!("{}{}", first, last).parse::<i32>().unwrap();
-    println!("{}", val);
-    println!("-----------------");
-    val
-}
-
-fn main() {
-    let result: i32 = fs::read_to_string("input.txt")
-        .expect("Something went wrong reading the file")
-        .lines()
-        .map(|line| parser(line))
-        .collect::<Vec<i32>>()
-        .iter()
-        .sum();
-    println!("{}", result);
+    let first = chars.next().and_then(|c| c.to_digit(10));
+    let last = chars.next_back().and_then(|c| c.to_digit(10)).or(first);
+    first
+        .zip(last)
+        .map(|(first, last)| (first * 10 + last) as i32)
+}
+
+// a digit as written in the line, "two" at 0..3 or "9" at 14..15
+#[derive(Debug, Clone, PartialEq)]
+struct Token<'a> {
+    text: &'a str,
+    span: Range<usize>,
+    digit: u8,
+}
+
+impl Token<'_> {
+    fn new(line: &str, span: Range<usize>, digit: u8) -> Token<'_> {
+        Token {
+            text: &line[span.clone()],
+            span,
+            digit,
+        }
+    }
+}
+
+// patterns keyed by one of their chars, ascii chars index straight into a table
+#[derive(Default)]
+struct Buckets {
+    ascii: Vec<Vec<(String, u8)>>,
+    other: HashMap<char, Vec<(String, u8)>>,
+}
+
+impl Buckets {
+    fn insert(&mut self, c: char, pattern: (String, u8)) {
+        if self.ascii.is_empty() {
+            self.ascii = vec![Vec::new(); 128];
+        }
+        match c.is_ascii() {
+            true => self.ascii[c as usize].push(pattern),
+            false => self.other.entry(c).or_default().push(pattern),
+        }
+    }
+
+    fn get(&self, c: char) -> &[(String, u8)] {
+        match c.is_ascii() {
+            true => &self.ascii[c as usize],
+            false => self.other.get(&c).map_or(&[], Vec::as_slice),
+        }
+    }
+}
+
+// the digits and, for part 2, the words of a vocabulary, bucketed by their
+// first and by their last char so each position only tries the patterns that
+// can start (or end) there
+struct Matcher {
+    by_first: Buckets,
+    by_last: Buckets,
+    ignore_case: bool,
+}
+
+impl Matcher {
+    fn new(mode: Mode, vocabulary: &Vocabulary) -> Self {
+        let ignore_case = vocabulary.ignore_case;
+        let mut patterns: Vec<(String, u8)> = (0..10u8).map(|d| (d.to_string(), d)).collect();
+        if mode == Mode::DigitsAndWords {
+            patterns.extend(vocabulary.words.iter().map(|(word, digit)| {
+                let word = word.chars().map(|c| fold(c, ignore_case)).collect();
+                (word, digit.parse().expect("vocabulary digits are 0 to 9"))
+            }));
+        }
+        let mut by_first = Buckets::default();
+        let mut by_last = Buckets::default();
+        for pattern in patterns {
+            let first = pattern.0.chars().next().expect("empty pattern");
+            let last = pattern.0.chars().next_back().expect("empty pattern");
+            by_first.insert(first, pattern.clone());
+            by_last.insert(last, pattern);
+        }
+        Self {
+            by_first,
+            by_last,
+            ignore_case,
+        }
+    }
+
+    // how many bytes of `chars` spell `pattern`, with case folded if asked to
+    fn match_len(
+        &self,
+        mut chars: impl Iterator<Item = char>,
+        pattern: impl Iterator<Item = char>,
+    ) -> Option<usize> {
+        let mut len = 0;
+        for p in pattern {
+            let c = chars.next()?;
+            if fold(c, self.ignore_case) != p {
+                return None;
+            }
+            len += c.len_utf8();
+        }
+        Some(len)
+    }
+
+    // scans forward from the start of the line
+    fn first_token<'a>(&self, line: &'a str) -> Option<Token<'a>> {
+        line.char_indices().find_map(|(i, c)| {
+            let candidates = self.by_first.get(fold(c, self.ignore_case));
+            candidates.iter().find_map(|(pattern, digit)| {
+                let len = self.match_len(line[i..].chars(), pattern.chars())?;
+                Some(Token::new(line, i..i + len, *digit))
+            })
+        })
+    }
+
+    // scans backward from the end of the line
+    fn last_token<'a>(&self, line: &'a str) -> Option<Token<'a>> {
+        line.char_indices().rev().find_map(|(i, c)| {
+            let end = i + c.len_utf8();
+            let candidates = self.by_last.get(fold(c, self.ignore_case));
+            candidates.iter().find_map(|(pattern, digit)| {
+                let len = self.match_len(line[..end].chars().rev(), pattern.chars().rev())?;
+                Some(Token::new(line, end - len..end, *digit))
+            })
+        })
+    }
+
+    fn first(&self, line: &str) -> Option<u8> {
+        self.first_token(line).map(|token| token.digit)
+    }
+
+    fn last(&self, line: &str) -> Option<u8> {
+        self.last_token(line).map(|token| token.digit)
+    }
+
+    // the first and last tokens, the same one when there's a single digit
+    fn tokens<'a>(&self, line: &'a str) -> Option<(Token<'a>, Token<'a>)> {
+        let first = self.first_token(line)?;
+        let last = self.last_token(line).unwrap_or_else(|| first.clone());
+        Some((first, last))
+    }
+
+    // same as `parser`
+    fn value(&self, line: &str) -> Option<i32> {
+        let first = self.first(line)?;
+        let last = self.last(line).unwrap_or(first);
+        Some((first * 10 + last) as i32)
+    }
+}
+
+fn fold(c: char, ignore_case: bool) -> char {
+    match ignore_case {
+        false => c,
+        true if c.is_ascii() => c.to_ascii_lowercase(),
+        true => c.to_lowercase().next().unwrap_or(c),
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Calibration {
+    digits: Sum,
+    digits_and_words: Sum,
+}
+
+// one mode's values, and the lines that had no digit to give one
+#[derive(Debug, Default, PartialEq)]
+struct Sum {
+    total: i64,
+    lines: usize,
+    digitless: usize,
+    first_digitless: Option<usize>,
+}
+
+impl Sum {
+    fn add(&mut self, line: usize, value: Option<i32>) {
+        self.lines += 1;
+        match value {
+            Some(value) => self.total += i64::from(value),
+            None => {
+                self.digitless += 1;
+                self.first_digitless.get_or_insert(line);
+            }
+        }
+    }
+}
+
+// what to do with a line that has no digit
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NoDigit {
+    Error,
+    Skip,
+    Zero,
+}
+
+impl FromStr for NoDigit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(NoDigit::Error),
+            "skip" => Ok(NoDigit::Skip),
+            "zero" => Ok(NoDigit::Zero),
+            _ => Err(format!(
+                "unknown policy {s:?}, expected error, skip or zero"
+            )),
+        }
+    }
+}
+
+impl Calibration {
+    fn get(&self, mode: Mode) -> &Sum {
+        match mode {
+            Mode::Digits => &self.digits,
+            Mode::DigitsAndWords => &self.digits_and_words,
+        }
+    }
+
+    // skipping a line and counting it as 0 give the same total, they only
+    // differ in how many lines took part
+    fn sum(&self, mode: Mode, policy: NoDigit) -> Result<i64, String> {
+        let sum = self.get(mode);
+        match (policy, sum.first_digitless) {
+            (NoDigit::Error, Some(line)) => Err(format!(
+                "line {line}: no digit ({} of {} lines have none)",
+                sum.digitless, sum.lines
+            )),
+            _ => Ok(sum.total),
+        }
+    }
+
+    fn summary(&self, mode: Mode, policy: NoDigit) -> Option<String> {
+        let sum = self.get(mode);
+        if sum.digitless == 0 {
+            return None;
+        }
+        let action = match policy {
+            NoDigit::Error => "rejected",
+            NoDigit::Skip => "skipped",
+            NoDigit::Zero => "counted as 0",
+        };
+        Some(format!(
+            "{} of {} lines have no digit, {action}",
+            sum.digitless, sum.lines
+        ))
+    }
+}
+
+// both sums from a single pass over the lines
+fn calibrate(input: &str, vocabulary: &Vocabulary) -> Calibration {
+    let digits = Matcher::new(Mode::Digits, vocabulary);
+    let words = Matcher::new(Mode::DigitsAndWords, vocabulary);
+    let mut calibration = Calibration::default();
+    for (i, line) in input.lines().enumerate() {
+        calibration.digits.add(i + 1, digits.value(line));
+        calibration.digits_and_words.add(i + 1, words.value(line));
+    }
+    calibration
+}
+
+// the same sums by tokenising every line
+fn calibrate_tokenized(input: &str, vocabulary: &Vocabulary) -> Calibration {
+    let mut calibration = Calibration::default();
+    for (i, line) in input.lines().enumerate() {
+        let digits = parser(line, Mode::Digits, vocabulary);
+        calibration.digits.add(i + 1, digits);
+        let words = parser(line, Mode::DigitsAndWords, vocabulary);
+        calibration.digits_and_words.add(i + 1, words);
+    }
+    calibration
+}
+
+const FIRST: &str = "\x1b[1;32m";
+const LAST: &str = "\x1b[1;36m";
+// a single digit, or words sharing letters like "eightwo"
+const BOTH: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+// the line with its first token in green and its last in cyan. Without
+// `ansi` the tokens are bracketed instead.
+fn highlight(line: &str, tokens: &Option<(Token, Token)>, ansi: bool) -> String {
+    let Some((first, last)) = tokens else {
+        return line.to_string();
+    };
+    let mut out = String::new();
+    let mut current = None;
+    for (i, c) in line.char_indices() {
+        let style = match (first.span.contains(&i), last.span.contains(&i)) {
+            (true, true) => Some(BOTH),
+            (true, false) => Some(FIRST),
+            (false, true) => Some(LAST),
+            (false, false) => None,
+        };
+        if style != current {
+            match ansi {
+                true => out.push_str(style.unwrap_or(RESET)),
+                false if current.is_some() && style.is_some() => out.push_str("]["),
+                false if current.is_some() => out.push(']'),
+                false => out.push('['),
+            }
+            current = style;
+        }
+        out.push(c);
+    }
+    if current.is_some() {
+        out.push_str(if ansi { RESET } else { "]" });
+    }
+    out
+}
+
+// one line per input line and mode: the highlighted line, where its first and
+// last tokens are and the value they make
+fn trace(input: &str, vocabulary: &Vocabulary, ansi: bool) -> String {
+    let matchers = [
+        (1, Matcher::new(Mode::Digits, vocabulary)),
+        (2, Matcher::new(Mode::DigitsAndWords, vocabulary)),
+    ];
+    let mut out = String::new();
+    for (i, line) in input.lines().enumerate() {
+        for (part, matcher) in &matchers {
+            let tokens = matcher.tokens(line);
+            let found = match &tokens {
+                Some((first, last)) => format!(
+                    "{:?} at {:?}, {:?} at {:?} -> {}",
+                    first.text,
+                    first.span,
+                    last.text,
+                    last.span,
+                    first.digit * 10 + last.digit
+                ),
+                None => "no digit".to_string(),
+            };
+            let highlighted = highlight(line, &tokens, ansi);
+            out.push_str(&format!(
+                "{:>4} part {part}: {highlighted}  {found}\n",
+                i + 1
+            ));
+        }
+    }
+    out
+}
+
+// full english cardinals for the extended calibration documents: "seven",
+// "twenty-one", "one hundred and five", "two thousand and twenty-three"
+const UNITS: [(&str, u64); 10] = [
+    ("zero", 0),
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+const TEENS: [(&str, u64); 10] = [
+    ("ten", 10),
+    ("eleven", 11),
+    ("twelve", 12),
+    ("thirteen", 13),
+    ("fourteen", 14),
+    ("fifteen", 15),
+    ("sixteen", 16),
+    ("seventeen", 17),
+    ("eighteen", 18),
+    ("nineteen", 19),
+];
+const TENS: [(&str, u64); 8] = [
+    ("twenty", 20),
+    ("thirty", 30),
+    ("forty", 40),
+    ("fifty", 50),
+    ("sixty", 60),
+    ("seventy", 70),
+    ("eighty", 80),
+    ("ninety", 90),
+];
+const SCALES: [(&str, u64); 3] = [
+    ("billion", 1_000_000_000),
+    ("million", 1_000_000),
+    ("thousand", 1_000),
+];
+
+fn starts_alphabetic(i: &str) -> bool {
+    i.chars().next().is_some_and(char::is_alphabetic)
+}
+
+// one of `words` as a whole word, in any case, so "six" doesn't match "sixty"
+fn number_word<'a>(
+    words: &'static [(&'static str, u64)],
+) -> impl FnMut(&'a str) -> IResult<&'a str, u64> {
+    move |i| {
+        for (word, value) in words {
+            let matched: IResult<&str, &str> = tag_no_case(*word)(i);
+            if let Ok((tail, _)) = matched {
+                if !starts_alphabetic(tail) {
+                    return Ok((tail, *value));
+                }
+            }
+        }
+        Err(nom::Err::Error(nom::error::Error::new(i, ErrorKind::Tag)))
+    }
+}
+
+// 0 to 99: "seven", "seventeen", "seventy", "seventy-seven", "seventy seven"
+fn below_hundred(i: &str) -> IResult<&str, u64> {
+    alt((
+        map(
+            pair(
+                number_word(&TENS),
+                opt(preceded(alt((tag("-"), space1)), number_word(&UNITS[1..]))),
+            ),
+            |(tens, units)| tens + units.unwrap_or(0),
+        ),
+        number_word(&TEENS),
+        number_word(&UNITS),
+    ))(i)
+}
+
+// " and " or just a space, between "hundred" or a scale and what follows
+fn and_separator(i: &str) -> IResult<&str, &str> {
+    alt((
+        map(
+            tuple((space1, tag_no_case("and"), space1)),
+            |(_, and, _)| and,
+        ),
+        space1,
+    ))(i)
+}
+
+// 0 to 999: "one hundred", "one hundred and five", "one hundred five"
+fn below_thousand(i: &str) -> IResult<&str, u64> {
+    alt((
+        map(
+            tuple((
+                number_word(&UNITS[1..]),
+                preceded(space1, number_word(&[("hundred", 100)])),
+                opt(preceded(and_separator, below_hundred)),
+            )),
+            |(hundreds, _, rest)| hundreds * 100 + rest.unwrap_or(0),
+        ),
+        below_hundred,
+    ))(i)
+}
+
+// groups below a thousand, each followed by a smaller scale than the last:
+// "two million three hundred thousand and six"
+fn cardinal(i: &str) -> IResult<&str, u64> {
+    let (mut i, mut group) = below_thousand(i)?;
+    let mut total = 0;
+    let mut last_scale = u64::MAX;
+    loop {
+        match preceded(space1, number_word(&SCALES))(i) {
+            Ok((tail, scale)) if scale < last_scale => {
+                total += group * scale;
+                last_scale = scale;
+                i = tail;
+            }
+            _ => break,
+        }
+        match preceded(and_separator, below_thousand)(i) {
+            Ok((tail, next)) => {
+                group = next;
+                i = tail;
+            }
+            Err(_) => {
+                group = 0;
+                break;
+            }
+        }
+    }
+    Ok((i, total + group))
+}
+
+// the whole run of digits, `None` for its value when it doesn't fit a u64
+fn digits(i: &str) -> IResult<&str, Option<u64>> {
+    map(digit1, |run: &str| run.parse().ok())(i)
+}
+
+// a number written out or in digits, as found in the line
+#[derive(Debug, Clone, PartialEq)]
+struct Number<'a> {
+    text: &'a str,
+    span: Range<usize>,
+    value: u64,
+}
+
+// every number in the line, longest first: "one hundred and five" is 105, not
+// 1 and 5. Words only count on word boundaries, digits anywhere. A digit run
+// too long for a u64 is skipped whole.
+fn numbers(line: &str) -> Vec<Number<'_>> {
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while let Some(c) = line[i..].chars().next() {
+        let rest = &line[i..];
+        let found = match c.is_ascii_digit() {
+            true => digits(rest).ok(),
+            false
+                if line[..i]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphabetic) =>
+            {
+                None
+            }
+            false => cardinal(rest).ok().map(|(tail, value)| (tail, Some(value))),
+        };
+        match found {
+            Some((tail, None)) => i = line.len() - tail.len(),
+            Some((tail, Some(value))) => {
+                let end = line.len() - tail.len();
+                numbers.push(Number {
+                    text: &line[i..end],
+                    span: i..end,
+                    value,
+                });
+                i = end;
+            }
+            None => i += c.len_utf8(),
+        }
+    }
+    numbers
+}
+
+// the first and last numbers of a line, the same one when there's only one
+fn first_and_last(line: &str) -> Option<(Number<'_>, Number<'_>)> {
+    let numbers = numbers(line);
+    Some((numbers.first()?.clone(), numbers.last()?.clone()))
+}
+
+// xorshift for the bench inputs, crosscheck's copy is only for tests
+fn next_rand(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+// lines of letters with a few digits and spelled out words mixed in
+fn synthetic_input(lines: usize, vocabulary: &Vocabulary, seed: u64) -> String {
+    let mut seed = seed;
+    let mut input = String::new();
+    for _ in 0..lines {
+        for _ in 0..8 {
+            match next_rand(&mut seed) % 16 {
+                0 => input.push(char::from(b'0' + (next_rand(&mut seed) % 10) as u8)),
+                1 => {
+                    let i = next_rand(&mut seed) as usize % vocabulary.words.len();
+                    input.push_str(&vocabulary.words[i].0);
+                }
+                _ => input.push(char::from(b'a' + (next_rand(&mut seed) % 26) as u8)),
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
+
+fn bench(lines: usize, vocabulary: &Vocabulary) {
+    let input = synthetic_input(lines, vocabulary, 42);
+    let start = Instant::now();
+    let tokenized = calibrate_tokenized(&input, vocabulary);
+    let tokenized_time = start.elapsed();
+    let start = Instant::now();
+    let scanned = calibrate(&input, vocabulary);
+    let scanned_time = start.elapsed();
+    assert_eq!(tokenized, scanned);
+    println!("{lines} lines, {} bytes", input.len());
+    println!("tokenized: {tokenized_time:?}");
+    println!("scanned:   {scanned_time:?}");
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    args.get(i + 1).map(String::as_str)
+}
+
+// --language en|es|de picks a built in vocabulary, --words <file> reads one,
+// --ignore-case matches words in any case
+fn vocabulary_from_args(args: &[String]) -> Result<Vocabulary, Box<dyn Error>> {
+    let mut vocabulary = match (flag_value(args, "--words"), flag_value(args, "--language")) {
+        (Some(path), _) => Vocabulary::parse(&fs::read_to_string(path)?)?,
+        (None, Some(language)) => Vocabulary::builtin(language)
+            .ok_or_else(|| format!("unknown language {language:?}, expected en, es or de"))?,
+        (None, None) => Vocabulary::from_words(&ENGLISH),
+    };
+    vocabulary.ignore_case = args.iter().any(|arg| arg == "--ignore-case");
+    Ok(vocabulary)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let vocabulary = vocabulary_from_args(&args)?;
+    if args.first().map(String::as_str) == Some("bench") {
+        let lines = match args.get(1).map(|lines| lines.parse()) {
+            Some(Ok(lines)) => lines,
+            _ => 1_000_000,
+        };
+        bench(lines, &vocabulary);
+        return Ok(());
+    }
+
+    let input = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
+    if args.iter().any(|arg| arg == "--cardinals") {
+        for (i, line) in input.lines().enumerate() {
+            match first_and_last(line) {
+                Some((first, last)) => println!(
+                    "{:>4}: first {} ({:?} at {:?}), last {} ({:?} at {:?})",
+                    i + 1,
+                    first.value,
+                    first.text,
+                    first.span,
+                    last.value,
+                    last.text,
+                    last.span
+                ),
+                None => println!("{:>4}: no number", i + 1),
+            }
+        }
+        return Ok(());
+    }
+    if args.iter().any(|arg| arg == "--trace") {
+        let ansi = env::var_os("NO_COLOR").is_none();
+        print!("{}", trace(&input, &vocabulary, ansi));
+    }
+    let calibration = calibrate(&input, &vocabulary);
+    let policy = match flag_value(&args, "--no-digit") {
+        Some(policy) => policy.parse()?,
+        None => NoDigit::Zero,
+    };
+    for (part, mode) in [(1, Mode::Digits), (2, Mode::DigitsAndWords)] {
+        if let Some(summary) = calibration.summary(mode, policy) {
+            println!("Part {part}: {summary}");
+        }
+        let sum = calibration
+            .sum(mode, policy)
+            .map_err(|err| format!("part {part}: {err}"))?;
+        println!("Part {part}: {sum}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        let english = Vocabulary::from_words(&ENGLISH);
+        assert_eq!(parser("a1b2c3d4e5f", Mode::Digits, &english), Some(15));
+        assert_eq!(parser("treb7uchet", Mode::Digits, &english), Some(77));
+        assert_eq!(parser("eightwothree", Mode::Digits, &english), None);
+        assert_eq!(
+            parser("eightwothree", Mode::DigitsAndWords, &english),
+            Some(83)
+        );
+        assert_eq!(parser("zoneight234", Mode::Digits, &english), Some(24));
+        assert_eq!(
+            parser("zoneight234", Mode::DigitsAndWords, &english),
+            Some(14)
+        );
+        assert_eq!(parser("xeightwo", Mode::DigitsAndWords, &english), Some(82));
+    }
+
+    #[test]
+    fn test_matcher() {
+        let english = Vocabulary::from_words(&ENGLISH);
+        let words = Matcher::new(Mode::DigitsAndWords, &english);
+        assert_eq!(words.first("xtwone3four"), Some(2));
+        assert_eq!(words.last("xtwone3four"), Some(4));
+        assert_eq!(words.last("eightwo"), Some(2));
+        assert_eq!(words.first("abc"), None);
+        assert_eq!(words.value("abc"), None);
+        assert_eq!(words.value("7pqrstsixteen"), Some(76));
+
+        let digits = Matcher::new(Mode::Digits, &english);
+        assert_eq!(digits.value("xtwone3four"), Some(33));
+
+        let mut german = Vocabulary::builtin("de").unwrap();
+        german.ignore_case = true;
+        assert_eq!(
+            Matcher::new(Mode::DigitsAndWords, &german).value("FÜNF3Acht"),
+            Some(58)
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let english = Vocabulary::from_words(&ENGLISH);
+        let words = Matcher::new(Mode::DigitsAndWords, &english);
+        let (first, last) = words.tokens("twoxyzxyzxyzxy9").unwrap();
+        assert_eq!(
+            first,
+            Token {
+                text: "two",
+                span: 0..3,
+                digit: 2
+            }
+        );
+        assert_eq!(
+            last,
+            Token {
+                text: "9",
+                span: 14..15,
+                digit: 9
+            }
+        );
+
+        let (first, last) = words.tokens("xeightwo").unwrap();
+        assert_eq!((first.span, last.span), (1..6, 5..8));
+        assert_eq!(words.tokens("ab7cd").unwrap().1.span, 2..3);
+        assert_eq!(words.tokens("abc"), None);
+
+        // spans are bytes of the line, not of the folded pattern
+        let mut german = Vocabulary::builtin("de").unwrap();
+        german.ignore_case = true;
+        let (first, last) = Matcher::new(Mode::DigitsAndWords, &german)
+            .tokens("xFÜNFyAcht")
+            .unwrap();
+        assert_eq!((first.text, first.span), ("FÜNF", 1..6));
+        assert_eq!((last.text, last.span), ("Acht", 7..11));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let english = Vocabulary::from_words(&ENGLISH);
+        let words = Matcher::new(Mode::DigitsAndWords, &english);
+        let highlighted = |line| highlight(line, &words.tokens(line), false);
+        assert_eq!(highlighted("xtwone3four"), "x[two]ne3[four]");
+        assert_eq!(highlighted("ab7cd"), "ab[7]cd");
+        assert_eq!(highlighted("xeightwo"), "x[eigh][t][wo]");
+        assert_eq!(highlighted("abc"), "abc");
+
+        assert_eq!(
+            highlight("a1b2", &words.tokens("a1b2"), true),
+            format!("a{FIRST}1{RESET}b{LAST}2{RESET}")
+        );
+
+        assert_eq!(
+            trace("eightwothree", &english, false),
+            "   1 part 1: eightwothree  no digit
+   1 part 2: [eight]wo[three]  \"eight\" at 0..5, \"three\" at 7..12 -> 83
+"
+        );
+    }
+
+    #[test]
+    fn test_cardinal() {
+        let value = |i| cardinal(i).unwrap();
+        assert_eq!(value("seven"), ("", 7));
+        assert_eq!(value("seventeen"), ("", 17));
+        assert_eq!(value("seventy"), ("", 70));
+        assert_eq!(value("twenty-one"), ("", 21));
+        assert_eq!(value("Twenty One"), ("", 21));
+        assert_eq!(value("one hundred and five"), ("", 105));
+        assert_eq!(value("nine hundred ninety-nine"), ("", 999));
+        assert_eq!(value("two thousand and twenty-three"), ("", 2023));
+        assert_eq!(
+            value("two million three hundred thousand and six"),
+            ("", 2_300_006)
+        );
+        // stops where the number does
+        assert_eq!(value("one hundred and apples"), (" and apples", 100));
+        assert_eq!(value("five thousand thousand"), (" thousand", 5000));
+        assert!(cardinal("sixtyfour").is_err());
+        assert!(cardinal("hundred").is_err());
+    }
+
+    #[test]
+    fn test_numbers() {
+        let line = "twenty-one geese, 7 swans and one hundred and five ducks";
+        let values: Vec<u64> = numbers(line).iter().map(|n| n.value).collect();
+        assert_eq!(values, vec![21, 7, 105]);
+
+        let (first, last) = first_and_last(line).unwrap();
+        assert_eq!(
+            first,
+            Number {
+                text: "twenty-one",
+                span: 0..10,
+                value: 21
+            }
+        );
+        assert_eq!(
+            (last.text, last.span, last.value),
+            ("one hundred and five", 30..50, 105)
+        );
+
+        // words inside other words don't count, digits do
+        assert_eq!(
+            numbers("someone ate ab12cd")
+                .iter()
+                .map(|n| n.value)
+                .collect::<Vec<_>>(),
+            vec![12]
+        );
+        // never re-read from inside a run that's too long
+        assert_eq!(numbers("123456789012345678901234"), vec![]);
+        let values: Vec<u64> = numbers("a99999999999999999999b7 two")
+            .iter()
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(values, vec![7, 2]);
+        assert_eq!(numbers("18446744073709551615")[0].value, u64::MAX);
+
+        let (first, last) = first_and_last("just eleven").unwrap();
+        assert_eq!(first, last);
+        assert_eq!(first_and_last("nothing here"), None);
+    }
+
+    #[test]
+    fn test_matcher_agrees_with_parser() {
+        for language in ["en", "es", "de"] {
+            let mut vocabulary = Vocabulary::builtin(language).unwrap();
+            for ignore_case in [false, true] {
+                vocabulary.ignore_case = ignore_case;
+                let input = synthetic_input(2000, &vocabulary, 7);
+                assert_eq!(
+                    calibrate(&input, &vocabulary),
+                    calibrate_tokenized(&input, &vocabulary)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let spanish = Vocabulary::builtin("es").unwrap();
+        assert_eq!(parser("xdosiete", Mode::DigitsAndWords, &spanish), Some(27));
+        assert_eq!(parser("two1nine", Mode::DigitsAndWords, &spanish), Some(11));
+
+        let mut german = Vocabulary::builtin("de").unwrap();
+        assert_eq!(
+            parser("fünfzig3acht", Mode::DigitsAndWords, &german),
+            Some(58)
+        );
+        assert_eq!(parser("FÜNF3Acht", Mode::DigitsAndWords, &german), Some(33));
+        german.ignore_case = true;
+        assert_eq!(parser("FÜNF3Acht", Mode::DigitsAndWords, &german), Some(58));
+
+        let custom = Vocabulary::parse("# binary\nzero 0\n\nuno 1\n").unwrap();
+        assert_eq!(
+            custom.words,
+            vec![
+                ("zero".to_string(), "0".to_string()),
+                ("uno".to_string(), "1".to_string())
+            ]
+        );
+        assert_eq!(parser("unozero", Mode::DigitsAndWords, &custom), Some(10));
+
+        assert_eq!(
+            Vocabulary::parse("one 1\ntwo 22").unwrap_err(),
+            "line 2: expected `<word> <digit>`, got \"two 22\""
+        );
+        assert!(Vocabulary::parse("# nothing").is_err());
+        assert_eq!(Vocabulary::builtin("fr"), None);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let part1 = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+        let english = Vocabulary::from_words(&ENGLISH);
+        let calibration = calibrate(part1, &english);
+        assert_eq!(calibration.sum(Mode::Digits, NoDigit::Error), Ok(142));
+        assert_eq!(calibration.summary(Mode::Digits, NoDigit::Error), None);
+
+        let part2 = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+        let calibration = calibrate(part2, &english);
+        assert_eq!(
+            calibration.sum(Mode::DigitsAndWords, NoDigit::Error),
+            Ok(281)
+        );
+        assert_eq!(
+            calibration.digits,
+            Sum {
+                total: 11 + 22 + 33 + 42 + 24 + 77,
+                lines: 7,
+                digitless: 1,
+                first_digitless: Some(2)
+            }
+        );
+
+        // past i32::MAX after about 21.7M lines of 99
+        let mut sum = Sum {
+            total: i32::MAX as i64,
+            ..Sum::default()
+        };
+        sum.add(1, Some(99));
+        assert_eq!(sum.total, i32::MAX as i64 + 99);
+    }
+
+    #[test]
+    fn test_no_digit_policy() {
+        let english = Vocabulary::from_words(&ENGLISH);
+        let calibration = calibrate("1abc2\nnothing\n\ntreb7uchet", &english);
+        assert_eq!(calibration.sum(Mode::Digits, NoDigit::Zero), Ok(12 + 77));
+        assert_eq!(calibration.sum(Mode::Digits, NoDigit::Skip), Ok(12 + 77));
+        assert_eq!(
+            calibration.sum(Mode::Digits, NoDigit::Error),
+            Err("line 2: no digit (2 of 4 lines have none)".to_string())
+        );
+        assert_eq!(
+            calibration.summary(Mode::Digits, NoDigit::Skip),
+            Some("2 of 4 lines have no digit, skipped".to_string())
+        );
+        assert_eq!(
+            calibration.summary(Mode::DigitsAndWords, NoDigit::Zero),
+            Some("2 of 4 lines have no digit, counted as 0".to_string())
+        );
+        assert_eq!(
+            calibration,
+            calibrate_tokenized("1abc2\nnothing\n\ntreb7uchet", &english)
+        );
+
+        assert_eq!("skip".parse(), Ok(NoDigit::Skip));
+        assert!("ignore".parse::<NoDigit>().is_err());
+    }
 }
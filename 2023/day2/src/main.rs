@@ -1,30 +1,65 @@
-use std::{cmp, fs};
+use std::{cmp, collections::BTreeMap, env, error::Error, fmt, fs};
 
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
-    character::complete::multispace0,
-    combinator::map,
-    multi::separated_list1,
-    sequence::{delimited, pair, preceded, terminated},
+    character::complete::{alpha1, digit1, multispace0},
+    combinator::{all_consuming, map, map_res, value},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
 #[derive(Debug, PartialEq)]
 struct Game(usize);
 
-#[derive(Debug, PartialEq)]
+// how many cubes of each colour. A colour that wasn't drawn isn't in the map,
+// which is not the same as "0 red".
+#[derive(Debug, Default, Clone, PartialEq)]
 struct CubeSet {
-    red: usize,
-    blue: usize,
-    green: usize,
+    counts: BTreeMap<String, usize>,
 }
 
-#[derive(Debug, PartialEq)]
-enum Token {
-    Red(usize),
-    Blue(usize),
-    Green(usize),
+impl<const N: usize> From<[(&str, usize); N]> for CubeSet {
+    fn from(counts: [(&str, usize); N]) -> Self {
+        CubeSet {
+            counts: counts
+                .iter()
+                .map(|(colour, count)| (colour.to_string(), *count))
+                .collect(),
+        }
+    }
+}
+
+impl CubeSet {
+    // `None` when the colour isn't in the set at all
+    fn get(&self, colour: &str) -> Option<usize> {
+        self.counts.get(colour).copied()
+    }
+
+    // multiset union: the most cubes of each colour seen in either set
+    fn union(&self, other: &CubeSet) -> CubeSet {
+        let mut counts = self.counts.clone();
+        for (colour, count) in &other.counts {
+            let max = counts.entry(colour.clone()).or_insert(0);
+            *max = cmp::max(*max, *count);
+        }
+        CubeSet { counts }
+    }
+
+    // the counts of every colour in the set multiplied together, colours that
+    // aren't in it don't take part
+    fn product(&self) -> usize {
+        self.counts.values().product()
+    }
+
+    // whether a bag holding `bag` could have given this set, a colour the bag
+    // doesn't have can't be drawn
+    fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.counts
+            .iter()
+            .all(|(colour, count)| *count <= bag.get(colour).unwrap_or(0))
+    }
 }
 
 fn parse_game(i: &str) -> IResult<&str, Game> {
@@ -42,75 +77,39 @@ fn parse_game(i: &str) -> IResult<&str, Game> {
     )(i)
 }
 
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(colour, count)| format!("{count} {colour}"))
+            .collect();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
 // 4 red, 8 blue, 3 green
+// exactly what was drawn, a colour named twice counts both times
 fn parse_set(i: &str) -> IResult<&str, CubeSet> {
     let (tail, tokens) = separated_list1(
         tag(","),
-        alt((
-            terminated(
-                delimited(
-                    multispace0,
-                    map(take_while1(|c| char::is_digit(c, 10)), |v: &str| {
-                        Token::Red(v.parse::<usize>().unwrap())
-                    }),
-                    multispace0,
-                ),
-                tag("red"),
-            ),
-            terminated(
-                delimited(
-                    multispace0,
-                    map(take_while1(|c| char::is_digit(c, 10)), |v: &str| {
-                        Token::Blue(v.parse::<usize>().unwrap())
-                    }),
-                    multispace0,
-                ),
-                tag("blue"),
-            ),
-            terminated(
-                delimited(
-                    multispace0,
-                    map(take_while1(|c| char::is_digit(c, 10)), |v: &str| {
-                        Token::Green(v.parse::<usize>().unwrap())
-                    }),
-                    multispace0,
-                ),
-                tag("green"),
+        pair(
+            delimited(
+                multispace0,
+                map(take_while1(|c| char::is_digit(c, 10)), |v: &str| {
+                    v.parse::<usize>().unwrap()
+                }),
+                multispace0,
             ),
-        )),
+            alpha1,
+        ),
     )(i)?;
-    let set = tokens.iter().fold(
-        // uncomment for part 1:
-        // CubeSet {
-        //     red: 0,
-        //     blue: 0,
-        //     green: 0,
-        // },
-        // uncomment for part 2:
-        CubeSet {
-            red: 1,
-            blue: 1,
-            green: 1,
-        },
-        |acc, x| match x {
-            Token::Red(v) => CubeSet {
-                red: *v,
-                blue: acc.blue,
-                green: acc.green,
-            },
-            Token::Blue(v) => CubeSet {
-                red: acc.red,
-                blue: *v,
-                green: acc.green,
-            },
-            Token::Green(v) => CubeSet {
-                red: acc.red,
-                blue: acc.blue,
-                green: *v,
-            },
-            _ => acc,
-        },
-    );
+    let set = tokens
+        .into_iter()
+        .fold(CubeSet::default(), |mut acc, (count, colour)| {
+            *acc.counts.entry(colour.to_string()).or_insert(0) += count;
+            acc
+        });
     Ok((tail, set))
 }
 
@@ -126,48 +125,544 @@ fn parse_game_samples(i: &str) -> IResult<&str, (Game, Vec<CubeSet>)> {
     )(i)
 }
 
-// use this parser for the day 2, part 1
-fn parser_part_1(i: &str) -> Option<usize> {
-    let (_, (game, cube_sets)) = parse_game_samples(i).unwrap();
+// the ids of the games a bag holding `bag` could have given
+fn possible(games: &[(Game, Vec<CubeSet>)], bag: &CubeSet) -> Vec<usize> {
+    games
+        .iter()
+        .filter(|(_, cube_sets)| cube_sets.iter().all(|set| set.fits_in(bag)))
+        .map(|(game, _)| game.0)
+        .collect()
+}
+
+// part 1: the sum of those ids
+fn possible_games(games: &[(Game, Vec<CubeSet>)], bag: &CubeSet) -> usize {
+    possible(games, bag).iter().sum()
+}
+
+// the first draw of a game that `bag` couldn't have given, counted from 0,
+// and the colours it has more of than the bag holds
+#[derive(Debug, PartialEq)]
+struct Impossible<'a> {
+    draw: usize,
+    set: &'a CubeSet,
+    over: Vec<(&'a str, usize)>,
+}
 
-    let is_out_of_bound = cube_sets
+fn first_impossible_draw<'a>(cube_sets: &'a [CubeSet], bag: &CubeSet) -> Option<Impossible<'a>> {
+    let (draw, set) = cube_sets
         .iter()
-        .any(|x| x.red > 12 || x.green > 13 || x.blue > 14);
+        .enumerate()
+        .find(|(_, set)| !set.fits_in(bag))?;
+    let over = set
+        .counts
+        .iter()
+        .filter(|(colour, count)| **count > bag.get(colour).unwrap_or(0))
+        .map(|(colour, count)| (colour.as_str(), *count))
+        .collect();
+    Some(Impossible { draw, set, over })
+}
+
+// the most cubes of each colour over all the sets, the smallest bag that
+// could have given them
+fn max(cube_sets: &[CubeSet]) -> CubeSet {
+    cube_sets
+        .iter()
+        .fold(CubeSet::default(), |acc, set| acc.union(set))
+}
+
+// the smallest bag that makes every game possible
+fn smallest_bag(games: &[(Game, Vec<CubeSet>)]) -> CubeSet {
+    games
+        .iter()
+        .fold(CubeSet::default(), |acc, (_, cube_sets)| {
+            acc.union(&max(cube_sets))
+        })
+}
 
-    if is_out_of_bound {
-        return None;
+// Inference assumes this sampling model: each draw takes its cubes from the
+// full bag without replacement, and they all go back before the next draw. A
+// colour the draw doesn't show was drawn 0 times. A draw is then a
+// multivariate hypergeometric sample:
+//
+//   P(draw | bag) = prod over colours C(bag[c], draw[c]) / C(bag total, draw total)
+//
+// and the draws are independent. Colours never drawn can't be inferred, so
+// candidate bags only hold the colours the game shows.
+const SAMPLING_MODEL: &str = "each draw takes its cubes without replacement and \
+returns them to the bag before the next draw";
+
+// ln(n!) for every n up to `max`
+fn ln_factorials(max: usize) -> Vec<f64> {
+    let mut table = vec![0.0; max + 1];
+    for n in 1..=max {
+        table[n] = table[n - 1] + (n as f64).ln();
     }
-    Some(game.0)
+    table
 }
 
-fn parser_part_2(i: &str) -> Option<usize> {
-    let (_, (_, cube_sets)) = parse_game_samples(i).unwrap();
+fn ln_choose(ln_factorials: &[f64], n: usize, k: usize) -> f64 {
+    match k <= n {
+        true => ln_factorials[n] - ln_factorials[k] - ln_factorials[n - k],
+        false => f64::NEG_INFINITY,
+    }
+}
 
-    let final_cubeset = cube_sets.iter().fold(
-        CubeSet {
-            red: 0,
-            blue: 0,
-            green: 0,
-        },
-        |acc, x| CubeSet {
-            red: cmp::max(acc.red, x.red),
-            blue: cmp::max(acc.blue, x.blue),
-            green: cmp::max(acc.green, x.green),
-        },
-    );
+// ln P(draws | bag), negative infinity when the bag couldn't have given them.
+// `ln_factorials` must reach the bag's total.
+fn log_likelihood(bag: &CubeSet, cube_sets: &[CubeSet], ln_factorials: &[f64]) -> f64 {
+    let total: usize = bag.counts.values().sum();
+    cube_sets
+        .iter()
+        .map(|set| {
+            if !set.fits_in(bag) {
+                return f64::NEG_INFINITY;
+            }
+            let drawn: usize = set.counts.values().sum();
+            let ways: f64 = bag
+                .counts
+                .iter()
+                .map(|(colour, count)| {
+                    ln_choose(ln_factorials, *count, set.get(colour).unwrap_or(0))
+                })
+                .sum();
+            ways - ln_choose(ln_factorials, total, drawn)
+        })
+        .sum()
+}
 
-    let result = final_cubeset.red * final_cubeset.green * final_cubeset.blue;
-    Some(result)
+#[derive(Debug, PartialEq)]
+struct Candidate {
+    bag: CubeSet,
+    log_likelihood: f64,
+    // the likelihood over that of all the candidates, the posterior under a
+    // uniform prior on them
+    probability: f64,
 }
 
-fn main() {
-    let result: usize = fs::read_to_string("input.txt")
-        .expect("Something went wrong reading the file")
-        .lines()
-        .map(|line| parser_part_2(line))
-        .flatten()
+// (slack + 1)^colours bags are tried, a game with many colours would need
+// more than fit in memory
+const MAX_CANDIDATES: usize = 1_000_000;
+// the ln factorial table holds one entry per cube of the largest bag
+const MAX_BAG_CUBES: usize = 1_000_000;
+
+// every bag holding between max(colour) and max(colour) + slack cubes of each
+// colour the game shows, most likely first. Ties go to the smaller bag.
+fn infer_bag(cube_sets: &[CubeSet], slack: usize) -> Result<Vec<Candidate>, String> {
+    let smallest = max(cube_sets);
+    let colours = smallest.counts.len() as u32;
+    match slack.checked_add(1).and_then(|n| n.checked_pow(colours)) {
+        Some(n) if n <= MAX_CANDIDATES => {}
+        _ => {
+            return Err(format!(
+                "{colours} colours with slack {slack} is more than {MAX_CANDIDATES} candidate bags, try a smaller --slack"
+            ))
+        }
+    }
+    let largest = smallest.counts.values().try_fold(0usize, |total, count| {
+        total.checked_add(count.checked_add(slack)?)
+    });
+    let ln_factorials = match largest {
+        Some(n) if n <= MAX_BAG_CUBES => ln_factorials(n),
+        _ => {
+            return Err(format!(
+                "bags with slack {slack} would hold more than {MAX_BAG_CUBES} cubes"
+            ))
+        }
+    };
+    let mut bags = vec![CubeSet::default()];
+    for (colour, count) in &smallest.counts {
+        bags = bags
+            .iter()
+            .flat_map(|bag| {
+                (*count..=count + slack).map(move |n| {
+                    let mut bag = bag.clone();
+                    bag.counts.insert(colour.clone(), n);
+                    bag
+                })
+            })
+            .collect();
+    }
+    let mut candidates: Vec<Candidate> = bags
+        .into_iter()
+        .map(|bag| Candidate {
+            log_likelihood: log_likelihood(&bag, cube_sets, &ln_factorials),
+            bag,
+            probability: 0.0,
+        })
+        .collect();
+    let best = candidates
+        .iter()
+        .map(|candidate| candidate.log_likelihood)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let total: f64 = candidates
+        .iter()
+        .map(|candidate| (candidate.log_likelihood - best).exp())
         .sum();
-    println!("{:?}", result);
+    for candidate in &mut candidates {
+        candidate.probability = (candidate.log_likelihood - best).exp() / total;
+    }
+    candidates.sort_by(|a, b| {
+        b.log_likelihood.total_cmp(&a.log_likelihood).then_with(|| {
+            let size = |bag: &CubeSet| bag.counts.values().sum::<usize>();
+            size(&a.bag).cmp(&size(&b.bag))
+        })
+    });
+    Ok(candidates)
+}
+
+// part 2: the power of the smallest bag that could have given each game. A
+// colour a game never shows doesn't count towards its power.
+fn power(games: &[(Game, Vec<CubeSet>)]) -> usize {
+    games
+        .iter()
+        .map(|(_, cube_sets)| max(cube_sets).product())
+        .sum()
+}
+
+// Filters select games with expressions like
+//
+//   max(red) > 10 && any(draw.blue == 0)
+//
+// comparing numbers with == != < <= > >=, joined by &&, || and !, with
+// parentheses. The numbers are:
+//
+//   12                 a literal
+//   id                 the game's id
+//   draws              how many draws the game has
+//   max(red)           the most red cubes in one draw, min() and sum() too
+//   draw.red           red cubes in the current draw, inside any() or all()
+//
+// any(...) holds when some draw makes it true, all(...) when every draw does.
+// A colour a draw doesn't show counts as 0 cubes of it.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(usize),
+    Id,
+    Draws,
+    Aggregate(Aggregate, String),
+    Draw(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Max,
+    Min,
+    Sum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Compare(Value, Op, Value),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Any(Box<Filter>),
+    All(Box<Filter>),
+}
+
+fn parse_value(i: &str) -> IResult<&str, Value> {
+    delimited(
+        multispace0,
+        alt((
+            map(map_res(digit1, str::parse), Value::Number),
+            map(
+                pair(
+                    alt((
+                        value(Aggregate::Max, tag("max")),
+                        value(Aggregate::Min, tag("min")),
+                        value(Aggregate::Sum, tag("sum")),
+                    )),
+                    delimited(
+                        pair(multispace0, tag("(")),
+                        delimited(multispace0, alpha1, multispace0),
+                        tag(")"),
+                    ),
+                ),
+                |(aggregate, colour): (Aggregate, &str)| {
+                    Value::Aggregate(aggregate, colour.to_string())
+                },
+            ),
+            map(preceded(tag("draw."), alpha1), |colour: &str| {
+                Value::Draw(colour.to_string())
+            }),
+            value(Value::Draws, tag("draws")),
+            value(Value::Id, tag("id")),
+        )),
+        multispace0,
+    )(i)
+}
+
+fn parse_op(i: &str) -> IResult<&str, Op> {
+    alt((
+        value(Op::Eq, tag("==")),
+        value(Op::Ne, tag("!=")),
+        value(Op::Le, tag("<=")),
+        value(Op::Ge, tag(">=")),
+        value(Op::Lt, tag("<")),
+        value(Op::Gt, tag(">")),
+    ))(i)
+}
+
+// (filter)
+fn parse_group(i: &str) -> IResult<&str, Filter> {
+    delimited(tag("("), parse_or, tag(")"))(i)
+}
+
+// a comparison, a negation, any(), all() or a filter in parentheses
+fn parse_atom(i: &str) -> IResult<&str, Filter> {
+    delimited(
+        multispace0,
+        alt((
+            map(preceded(tag("!"), parse_atom), |f| Filter::Not(Box::new(f))),
+            map(preceded(tag("any"), parse_group), |f| {
+                Filter::Any(Box::new(f))
+            }),
+            map(preceded(tag("all"), parse_group), |f| {
+                Filter::All(Box::new(f))
+            }),
+            parse_group,
+            map(tuple((parse_value, parse_op, parse_value)), |(a, op, b)| {
+                Filter::Compare(a, op, b)
+            }),
+        )),
+        multispace0,
+    )(i)
+}
+
+fn parse_and(i: &str) -> IResult<&str, Filter> {
+    let (tail, (first, rest)) = pair(parse_atom, many0(preceded(tag("&&"), parse_atom)))(i)?;
+    let filter = rest
+        .into_iter()
+        .fold(first, |a, b| Filter::And(Box::new(a), Box::new(b)));
+    Ok((tail, filter))
+}
+
+fn parse_or(i: &str) -> IResult<&str, Filter> {
+    let (tail, (first, rest)) = pair(parse_and, many0(preceded(tag("||"), parse_and)))(i)?;
+    let filter = rest
+        .into_iter()
+        .fold(first, |a, b| Filter::Or(Box::new(a), Box::new(b)));
+    Ok((tail, filter))
+}
+
+impl Filter {
+    fn parse(i: &str) -> Result<Filter, String> {
+        let filter = match all_consuming(parse_or)(i) {
+            Ok((_, filter)) => filter,
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                return Err(format!("invalid filter at {:?}", e.input))
+            }
+            Err(nom::Err::Incomplete(_)) => return Err("incomplete filter".to_string()),
+        };
+        filter.check(false)?;
+        Ok(filter)
+    }
+
+    // `draw.<colour>` needs a draw, so it can only be used inside any() or all()
+    fn check(&self, in_draw: bool) -> Result<(), String> {
+        match self {
+            Filter::Compare(a, _, b) => {
+                for value in [a, b] {
+                    if let (Value::Draw(colour), false) = (value, in_draw) {
+                        return Err(format!(
+                            "`draw.{colour}` can only be used inside any() or all()"
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Filter::And(a, b) | Filter::Or(a, b) => a.check(in_draw).and(b.check(in_draw)),
+            Filter::Not(f) => f.check(in_draw),
+            Filter::Any(f) | Filter::All(f) => f.check(true),
+        }
+    }
+
+    fn matches(&self, game: &Game, draws: &[CubeSet]) -> bool {
+        self.eval(game, draws, None)
+    }
+
+    fn eval(&self, game: &Game, draws: &[CubeSet], draw: Option<&CubeSet>) -> bool {
+        match self {
+            Filter::Compare(a, op, b) => {
+                let (a, b) = (a.eval(game, draws, draw), b.eval(game, draws, draw));
+                match op {
+                    Op::Eq => a == b,
+                    Op::Ne => a != b,
+                    Op::Lt => a < b,
+                    Op::Le => a <= b,
+                    Op::Gt => a > b,
+                    Op::Ge => a >= b,
+                }
+            }
+            Filter::And(a, b) => a.eval(game, draws, draw) && b.eval(game, draws, draw),
+            Filter::Or(a, b) => a.eval(game, draws, draw) || b.eval(game, draws, draw),
+            Filter::Not(f) => !f.eval(game, draws, draw),
+            Filter::Any(f) => draws.iter().any(|d| f.eval(game, draws, Some(d))),
+            Filter::All(f) => draws.iter().all(|d| f.eval(game, draws, Some(d))),
+        }
+    }
+}
+
+impl Value {
+    fn eval(&self, game: &Game, draws: &[CubeSet], draw: Option<&CubeSet>) -> usize {
+        let count = |set: &CubeSet, colour: &str| set.get(colour).unwrap_or(0);
+        match self {
+            Value::Number(n) => *n,
+            Value::Id => game.0,
+            Value::Draws => draws.len(),
+            Value::Aggregate(aggregate, colour) => {
+                let counts = draws.iter().map(|set| count(set, colour));
+                match aggregate {
+                    Aggregate::Max => counts.max().unwrap_or(0),
+                    Aggregate::Min => counts.min().unwrap_or(0),
+                    Aggregate::Sum => counts.sum(),
+                }
+            }
+            Value::Draw(colour) => count(draw.expect("checked by Filter::check"), colour),
+        }
+    }
+}
+
+fn parse_games(input: &str) -> Vec<(Game, Vec<CubeSet>)> {
+    input
+        .lines()
+        .map(|line| parse_game_samples(line).unwrap().1)
+        .collect()
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    args.get(i + 1).map(String::as_str)
+}
+
+// the bag as a draw, "12 red, 13 green, 14 blue". A bag file may also put
+// each colour on its own line, with `#` comments.
+fn parse_bag(i: &str) -> Result<CubeSet, String> {
+    let bag: Vec<&str> = i
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    match parse_set(&bag.join(", ")) {
+        Ok(("", bag)) => Ok(bag),
+        _ => Err(format!(
+            "expected a bag like \"12 red, 13 green\", got {i:?}"
+        )),
+    }
+}
+
+// --bag "<counts>" or --bag-file <path>, the puzzle's bag otherwise
+fn bag_from_args(args: &[String]) -> Result<CubeSet, Box<dyn Error>> {
+    match (flag_value(args, "--bag"), flag_value(args, "--bag-file")) {
+        (Some(bag), _) => Ok(parse_bag(bag)?),
+        (None, Some(path)) => Ok(parse_bag(&fs::read_to_string(path)?)?),
+        (None, None) => Ok(CubeSet::from([("red", 12), ("green", 13), ("blue", 14)])),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let bag = bag_from_args(&args)?;
+    let games = parse_games(
+        &fs::read_to_string("input.txt").expect("Something went wrong reading the file"),
+    );
+
+    match args.first().map(String::as_str) {
+        Some("possible") => {
+            let ids: Vec<String> = possible(&games, &bag)
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+            println!("{}", ids.join(" "));
+        }
+        Some("smallest") => println!("{}", smallest_bag(&games)),
+        Some("filter") => {
+            let filter = args
+                .get(1)
+                .ok_or("usage: filter <expression> [--count|--sum]")?;
+            let filter = Filter::parse(filter)?;
+            let ids: Vec<usize> = games
+                .iter()
+                .filter(|(game, draws)| filter.matches(game, draws))
+                .map(|(game, _)| game.0)
+                .collect();
+            if args.iter().any(|arg| arg == "--count") {
+                println!("{}", ids.len());
+            } else if args.iter().any(|arg| arg == "--sum") {
+                println!("{}", ids.iter().sum::<usize>());
+            } else {
+                let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                println!("{}", ids.join(" "));
+            }
+        }
+        Some("infer") => {
+            let id = args
+                .get(1)
+                .ok_or("usage: infer <game id> [--slack N] [--top N]")?;
+            let id = id.parse::<usize>()?;
+            let slack = flag_value(&args, "--slack").map_or(Ok(10), str::parse)?;
+            let top = flag_value(&args, "--top").map_or(Ok(10), str::parse)?;
+            let (_, cube_sets) = games
+                .iter()
+                .find(|(game, _)| game.0 == id)
+                .ok_or(format!("no game {id}"))?;
+            println!("model: {SAMPLING_MODEL}");
+            println!("smallest bag: {}", max(cube_sets));
+            for (rank, candidate) in infer_bag(cube_sets, slack)?.iter().take(top).enumerate() {
+                println!(
+                    "{:>3}. {}  ln L = {:.3}  p = {:.4}",
+                    rank + 1,
+                    candidate.bag,
+                    candidate.log_likelihood,
+                    candidate.probability
+                );
+            }
+        }
+        Some("why") => {
+            let id = args
+                .get(1)
+                .ok_or("usage: why <game id>")?
+                .parse::<usize>()?;
+            let (_, cube_sets) = games
+                .iter()
+                .find(|(game, _)| game.0 == id)
+                .ok_or(format!("no game {id}"))?;
+            match first_impossible_draw(cube_sets, &bag) {
+                Some(impossible) => {
+                    let over: Vec<String> = impossible
+                        .over
+                        .iter()
+                        .map(|(colour, count)| {
+                            let held = bag.get(colour).unwrap_or(0);
+                            format!("{count} {colour} but the bag holds {held}")
+                        })
+                        .collect();
+                    println!(
+                        "game {id}, draw {} ({}): {}",
+                        impossible.draw + 1,
+                        impossible.set,
+                        over.join(", ")
+                    );
+                }
+                None => println!("game {id} is possible with {bag}"),
+            }
+        }
+        _ => {
+            println!("Part 1: {}", possible_games(&games, &bag));
+            println!("Part 2: {}", power(&games));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -198,82 +693,269 @@ mod test {
     #[test]
     fn test_parse_set() {
         let input = "4 red, 8 blue, 3 green";
-        let expected = CubeSet {
-            red: 4,
-            blue: 8,
-            green: 3,
-        };
+        let expected = CubeSet::from([("red", 4), ("blue", 8), ("green", 3)]);
 
         let (_, output) = parse_set(input).unwrap();
         assert_eq!(output, expected);
 
         let input = "2 green, 12 blue";
-        let expected = CubeSet {
-            red: 0,
-            blue: 12,
-            green: 2,
-        };
+        let expected = CubeSet::from([("blue", 12), ("green", 2)]);
 
         let (_, output) = parse_set(input).unwrap();
         assert_eq!(output, expected);
 
         let input = "8 green";
-        let expected = CubeSet {
-            red: 0,
-            blue: 0,
-            green: 8,
-        };
+        let expected = CubeSet::from([("green", 8)]);
 
         let (_, output) = parse_set(input).unwrap();
         assert_eq!(output, expected);
     }
 
+    #[test]
+    fn test_parse_set_absence() {
+        let (_, output) = parse_set("0 red, 2 green").unwrap();
+        assert_eq!(output.get("red"), Some(0));
+        assert_eq!(output.get("green"), Some(2));
+        assert_eq!(output.get("blue"), None);
+
+        let (_, output) = parse_set("1 red, 2 red").unwrap();
+        assert_eq!(output, CubeSet::from([("red", 3)]));
+    }
+
     #[test]
     fn test_parse_cube_sets() {
         let input = "1 green, 7 red; 1 green, 9 red, 3 blue; 4 blue, 5 red";
         let expected = vec![
-            CubeSet {
-                red: 7,
-                blue: 0,
-                green: 1,
-            },
-            CubeSet {
-                red: 9,
-                blue: 3,
-                green: 1,
-            },
-            CubeSet {
-                red: 5,
-                blue: 4,
-                green: 0,
-            },
+            CubeSet::from([("red", 7), ("green", 1)]),
+            CubeSet::from([("red", 9), ("blue", 3), ("green", 1)]),
+            CubeSet::from([("red", 5), ("blue", 4)]),
         ];
 
         let (_, output) = parse_cube_sets(input).unwrap();
         assert_eq!(output, expected);
     }
 
+    #[test]
+    fn test_cube_set() {
+        let a = CubeSet::from([("red", 4), ("teal", 2)]);
+        let b = CubeSet::from([("red", 1), ("blue", 6)]);
+        assert_eq!(
+            a.union(&b),
+            CubeSet::from([("red", 4), ("teal", 2), ("blue", 6)])
+        );
+        assert_eq!(max(&[a.clone(), b.clone()]).product(), 4 * 2 * 6);
+        assert_eq!(a.get("blue"), None);
+
+        let bag = CubeSet::from([("red", 4), ("teal", 2), ("blue", 5)]);
+        assert!(a.fits_in(&bag));
+        assert!(!b.fits_in(&bag));
+    }
+
+    #[test]
+    fn test_parts() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(input);
+        let bag = CubeSet::from([("red", 12), ("green", 13), ("blue", 14)]);
+        assert_eq!(possible_games(&games, &bag), 8);
+        assert_eq!(power(&games), 2286);
+
+        // any colour names
+        let games = parse_games("Game 7: 2 teal, 1 mauve; 3 teal");
+        assert_eq!(games[0].1[0].get("mauve"), Some(1));
+        assert_eq!(max(&games[0].1).get("teal"), Some(3));
+        assert_eq!(max(&games[0].1).get("red"), None);
+    }
+
+    #[test]
+    fn test_queries() {
+        let games = parse_games(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+        );
+        let bag = parse_bag("12 red, 13 green, 14 blue").unwrap();
+        assert_eq!(possible(&games, &bag), vec![1]);
+
+        let smallest = smallest_bag(&games);
+        assert_eq!(smallest.to_string(), "15 blue, 13 green, 20 red");
+        assert_eq!(possible(&games, &smallest), vec![1, 3, 4]);
+
+        assert_eq!(
+            first_impossible_draw(&games[1].1, &bag),
+            Some(Impossible {
+                draw: 0,
+                set: &games[1].1[0],
+                over: vec![("red", 20)]
+            })
+        );
+        assert_eq!(
+            first_impossible_draw(&games[2].1, &bag).unwrap().over,
+            vec![("blue", 15), ("red", 14)]
+        );
+        assert_eq!(first_impossible_draw(&games[0].1, &bag), None);
+
+        // a colour the bag doesn't hold can't be drawn
+        let no_green = parse_bag("# no green\n20 red\n20 blue\n").unwrap();
+        assert_eq!(
+            first_impossible_draw(&games[0].1, &no_green).unwrap().over,
+            vec![("green", 2)]
+        );
+        assert!(parse_bag("twelve red").is_err());
+    }
+
+    #[test]
+    fn test_log_likelihood() {
+        let bag = CubeSet::from([("red", 2), ("blue", 1)]);
+        let one_each = CubeSet::from([("red", 1), ("blue", 1)]);
+        let two_red = CubeSet::from([("red", 2)]);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let ln_factorials = ln_factorials(3);
+        assert!(close(
+            log_likelihood(&bag, std::slice::from_ref(&one_each), &ln_factorials),
+            (2.0f64 / 3.0).ln()
+        ));
+        assert!(close(
+            log_likelihood(&bag, std::slice::from_ref(&two_red), &ln_factorials),
+            (1.0f64 / 3.0).ln()
+        ));
+        assert!(close(
+            log_likelihood(&bag, &[one_each, two_red], &ln_factorials),
+            (2.0f64 / 9.0).ln()
+        ));
+        assert_eq!(
+            log_likelihood(&bag, &[CubeSet::from([("blue", 2)])], &ln_factorials),
+            f64::NEG_INFINITY
+        );
+    }
+
+    #[test]
+    fn test_infer_bag() {
+        // one colour: every bag gives the draws for sure, the smallest comes first
+        let draws = [CubeSet::from([("red", 3)])];
+        let candidates = infer_bag(&draws, 4).unwrap();
+        assert_eq!(candidates.len(), 5);
+        assert_eq!(candidates[0].bag, CubeSet::from([("red", 3)]));
+        assert!(candidates
+            .iter()
+            .all(|c| (c.probability - 0.2).abs() < 1e-9));
+
+        // always seeing as many of each points at an even bag, not at the
+        // element-wise max
+        let (_, (_, draws)) =
+            parse_game_samples("Game 1: 2 red, 2 blue; 2 red, 2 blue; 2 red, 2 blue; 4 red")
+                .unwrap();
+        let candidates = infer_bag(&draws, 6).unwrap();
+        assert_eq!(candidates.len(), 7 * 7);
+        let best = &candidates[0].bag;
+        assert_ne!(best, &max(&draws));
+        assert!(
+            candidates[0].log_likelihood
+                >= log_likelihood(&max(&draws), &draws, &ln_factorials(4 + 2 + 2 * 6))
+        );
+        assert!(candidates
+            .windows(2)
+            .all(|w| w[0].log_likelihood >= w[1].log_likelihood));
+        let total: f64 = candidates.iter().map(|c| c.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // 11^8 bags is past the cap
+        let (_, (_, draws)) = parse_game_samples(
+            "Game 1: 1 red, 1 blue, 1 green, 1 black, 1 white, 1 pink, 1 grey, 1 brown",
+        )
+        .unwrap();
+        assert!(infer_bag(&draws, 10).is_err());
+        assert!(infer_bag(&draws, usize::MAX).is_err());
+        assert_eq!(infer_bag(&draws, 2).unwrap().len(), 3usize.pow(8));
+    }
+
+    #[test]
+    fn test_infer_bag_too_many_cubes() {
+        let draws = [CubeSet::from([("red", 1_000_000_000)])];
+        assert_eq!(
+            infer_bag(&draws, 1).unwrap_err(),
+            "bags with slack 1 would hold more than 1000000 cubes"
+        );
+        let draws = [CubeSet::from([("red", 3)])];
+        assert!(infer_bag(&draws, usize::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            Filter::parse("max(red) > 10 && any(draw.blue == 0)"),
+            Ok(Filter::And(
+                Box::new(Filter::Compare(
+                    Value::Aggregate(Aggregate::Max, "red".to_string()),
+                    Op::Gt,
+                    Value::Number(10)
+                )),
+                Box::new(Filter::Any(Box::new(Filter::Compare(
+                    Value::Draw("blue".to_string()),
+                    Op::Eq,
+                    Value::Number(0)
+                ))))
+            ))
+        );
+        // && binds tighter than ||
+        assert_eq!(
+            Filter::parse("id == 1 || id == 2 && draws > 9"),
+            Filter::parse("id == 1 || (id == 2 && draws > 9)")
+        );
+        assert_eq!(
+            Filter::parse("draw.red > 1"),
+            Err("`draw.red` can only be used inside any() or all()".to_string())
+        );
+        assert_eq!(
+            Filter::parse("max(red) >> 1"),
+            Err("invalid filter at \"> 1\"".to_string())
+        );
+        assert!(Filter::parse("any(draw.red > 1").is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let games = parse_games(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        );
+        let select = |filter: &str| -> Vec<usize> {
+            let filter = Filter::parse(filter).unwrap();
+            games
+                .iter()
+                .filter(|(game, draws)| filter.matches(game, draws))
+                .map(|(game, _)| game.0)
+                .collect()
+        };
+        assert_eq!(select("max(red) > 10 && any(draw.blue == 0)"), vec![3, 4]);
+        assert_eq!(select("max(red) > 10"), vec![3, 4]);
+        assert_eq!(select("!(draws == 3)"), vec![5]);
+        assert_eq!(select("sum(green) >= 7 || min(blue) > 0"), vec![2, 3, 4, 5]);
+        assert_eq!(select("all(draw.green > 0) && id != 3"), vec![2, 4, 5]);
+
+        // part 1 as a filter
+        let bag = CubeSet::from([("red", 12), ("green", 13), ("blue", 14)]);
+        assert_eq!(
+            select("all(draw.red <= 12 && draw.green <= 13 && draw.blue <= 14)"),
+            possible(&games, &bag)
+        );
+    }
+
     #[test]
     fn test_parse_game_samples() {
         let input = "Game 1: 2 green, 12 blue; 6 red, 6 blue; 8 blue, 5 green, 5 red";
         let expected = (
             Game(1),
             vec![
-                CubeSet {
-                    red: 0,
-                    blue: 12,
-                    green: 2,
-                },
-                CubeSet {
-                    red: 6,
-                    blue: 6,
-                    green: 0,
-                },
-                CubeSet {
-                    red: 5,
-                    blue: 8,
-                    green: 5,
-                },
+                CubeSet::from([("blue", 12), ("green", 2)]),
+                CubeSet::from([("red", 6), ("blue", 6)]),
+                CubeSet::from([("red", 5), ("blue", 8), ("green", 5)]),
             ],
         );
         let (_, output) = parse_game_samples(input).unwrap();
@@ -49,21 +49,19 @@ fn is_adjacent(x: usize, y: usize, to_x: usize, to_y: usize) -> bool {
     false
 }
 
-fn main() {
+// symbols of the schematic, each one holding the numbers attached to it. A
+// number next to several symbols only goes to the first one found.
+fn find_symbols(input: &str) -> Vec<Symbol> {
     let mut numbers: Vec<Number> = Vec::new();
     let mut symbols: Vec<Symbol> = Vec::new();
-    for (y, line) in fs::read_to_string("input.txt")
-        .expect("Something went wrong reading the file")
-        .lines()
-        .enumerate()
-    {
+    for (y, line) in input.lines().enumerate() {
         let mut number_char = String::new();
         let mut number_positions: Vec<(usize, usize)> = Vec::new();
         for (x, c) in line.chars().enumerate() {
-            if c.is_digit(10) {
+            if c.is_ascii_digit() {
                 number_char.push(c);
                 number_positions.push((x, y));
-            } else if number_char.len() > 0 {
+            } else if !number_char.is_empty() {
                 let newnum = number_char.parse::<usize>().expect("not a number");
                 numbers.push(Number::new(newnum, number_positions));
                 number_char = String::new();
@@ -74,20 +72,17 @@ fn main() {
                 symbols.push(Symbol::new(x, y, c));
             }
         }
-        if number_char.len() > 0 {
+        if !number_char.is_empty() {
             let newnum = number_char.parse::<usize>().expect("not a number");
             numbers.push(Number::new(newnum, number_positions));
         }
     }
-    let mut num: i64 = 0;
 
     for number in numbers.iter() {
         for symbol in symbols.iter_mut() {
             let mut found = false;
             for position in number.positions.iter() {
                 if is_adjacent(symbol.x, symbol.y, position.0, position.1) {
-                    num += number.value as i64;
-
                     found = true;
                     symbol.adjacent.push(number.clone());
                     break;
@@ -98,16 +93,67 @@ fn main() {
             }
         }
     }
-    println!("{:?}", num);
-
-    // Part 2
-    let s: i64 = symbols.into_iter().map(|v| {
-        if v.adjacent.len() > 1 {
-            v.adjacent.iter().fold(1, |acc, x| acc * x.value as i64)
-        } else {
-            return 0;
-        }
-    }).sum();
-    println!("{:?}", s);
+    symbols
+}
+
+fn part1(symbols: &[Symbol]) -> i64 {
+    symbols
+        .iter()
+        .flat_map(|v| v.adjacent.iter())
+        .map(|x| x.value as i64)
+        .sum()
+}
+
+fn part2(symbols: &[Symbol]) -> i64 {
+    symbols
+        .iter()
+        .map(|v| {
+            if v.adjacent.len() > 1 {
+                v.adjacent.iter().fold(1, |acc, x| acc * x.value as i64)
+            } else {
+                0
+            }
+        })
+        .sum()
+}
 
+fn main() {
+    let raw = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
+    let symbols = find_symbols(&raw);
+    println!("{:?}", part1(&symbols));
+    println!("{:?}", part2(&symbols));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snapshot::assert_snapshot;
+
+    const SAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_parts() {
+        let symbols = find_symbols(SAMPLE);
+        assert_eq!(part1(&symbols), 4361);
+        assert_eq!(part2(&symbols), 467835);
+
+        let symbols = find_symbols(include_str!("../sample1.txt"));
+        assert_eq!(part1(&symbols), 930);
+        assert_eq!(part2(&symbols), 57821);
+    }
+
+    #[test]
+    fn test_find_symbols_snapshot() {
+        assert_snapshot!("sample_symbols", find_symbols(SAMPLE));
+        assert_snapshot!("sample1_symbols", find_symbols(include_str!("../sample1.txt")));
+    }
 }
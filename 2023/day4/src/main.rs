@@ -58,35 +58,95 @@ fn get_points(i: &str) -> u64 {
     return wins as u64;
 }
 
-fn main() {
-    let result: Vec<(usize, u64)> = fs::read_to_string("input.txt")
-        .expect("Something went wrong reading the file")
-        .lines()
-        .map(|line| get_points(line))
-        .enumerate()
-        .collect();
+// part 2, simulating every won copy one by one
+fn part2_queue(i: &str) -> u64 {
+    let result: Vec<(usize, u64)> = i.lines().map(get_points).enumerate().collect();
 
-    // part 2
     let mut options = VecDeque::from(result.clone());
     let mut count = 0;
-    while options.len() > 0 {
-        let (position, value) = options.pop_front().unwrap();
+    while let Some((position, value)) = options.pop_front() {
         count += 1;
         if value == 0 {
             continue;
         }
         let next_index_start = position + 1;
         let next_index = next_index_start + value as usize;
-        for i in next_index_start..next_index {
-            options.push_back((i, result[i].1));
+        options.extend(&result[next_index_start..next_index]);
+    }
+    count
+}
+
+// part 2, counting copies per card instead of queueing each of them
+fn part2_dp(i: &str) -> u64 {
+    let wins: Vec<u64> = i.lines().map(get_points).collect();
+
+    let mut copies = vec![1u64; wins.len()];
+    for (position, value) in wins.iter().enumerate() {
+        let next_index_start = position + 1;
+        let next_index = next_index_start + *value as usize;
+        let current = copies[position];
+        for copy in copies[next_index_start..next_index].iter_mut() {
+            *copy += current;
         }
     }
-    println!("{:?}", count);
+    copies.iter().sum()
+}
+
+type Solver = fn(&str) -> u64;
+
+// every implementation of part 2, `main` runs the first one
+const PART2_SOLVERS: [(&str, Solver); 2] = [("queue", part2_queue), ("dp", part2_dp)];
+
+fn main() {
+    let raw = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
+    let (_, solver) = PART2_SOLVERS[0];
+    println!("{:?}", solver(&raw));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crosscheck::{assert_solvers_agree, next_rand};
+
+    const SAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    // cards never win past the end of the table, like the real input
+    fn generate_input(seed: &mut u64) -> String {
+        let cards = 1 + next_rand(seed) % 12;
+        (0..cards)
+            .map(|card| {
+                let max_wins = (cards - card - 1).min(5);
+                let wins = next_rand(seed) % (max_wins + 1);
+                let ticket: Vec<String> = (1..=5)
+                    .map(|n| if n <= wins { n } else { n + 50 }.to_string())
+                    .collect();
+                format!("Card {}: 1 2 3 4 5 | {}", card + 1, ticket.join(" "))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_part2() {
+        for (name, solver) in PART2_SOLVERS {
+            assert_eq!(solver(SAMPLE), 30, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_part2_solvers_agree() {
+        assert_solvers_agree(SAMPLE, PART2_SOLVERS);
+
+        let mut seed = 0x2023_0004;
+        for _ in 0..50 {
+            assert_solvers_agree(&generate_input(&mut seed), PART2_SOLVERS);
+        }
+    }
 
     #[test]
     fn test_parse_numbers() {
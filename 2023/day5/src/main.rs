@@ -191,7 +191,7 @@ fn parse_map(
 
 fn source_dest_map(val: u64, triplet: &Triplet) -> Option<u64> {
     let (start, end) = triplet.as_source_range();
-    if val >= start && val < end {
+    if val >= start && val <= end {
         let dest = triplet.as_destination_range();
         let r = val - start;
         return Some(r + dest.0);
@@ -223,99 +223,86 @@ fn overlap_range_triplet(range: &Range, triplet: &Triplet) -> Option<Range> {
     Some((dest_range.0 + overlap_diff.0, dest_range.1 - overlap_diff.1))
 }
 
+// split `range` over the triplets, the parts no triplet covers map to themselves
+fn map_range(range: &Range, triplets: &Triplets) -> Vec<Range> {
+    let mut mapped = Vec::new();
+    let mut unmapped = vec![*range];
+    for triplet in triplets {
+        let mut rest = Vec::new();
+        for range in unmapped {
+            match overlap_range(&range, &triplet.as_source_range()) {
+                Some((start, end)) => {
+                    mapped.push(overlap_range_triplet(&(start, end), triplet).unwrap());
+                    if range.0 < start {
+                        rest.push((range.0, start - 1));
+                    }
+                    if end < range.1 {
+                        rest.push((end + 1, range.1));
+                    }
+                }
+                None => rest.push(range),
+            }
+        }
+        unmapped = rest;
+    }
+    mapped.extend(unmapped);
+    mapped
+}
+
 fn parse_part1(input: &mut &str) -> PResult<u64> {
     let (seeds, soil, fertilizer, water, light, temp, humidity, locations) =
         parse_map.parse_next(input).expect("Failed to parse map");
 
-    let loc = seeds
-        .into_iter()
-        .map(|seed| {
-            let r = soil
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
-        })
-        .flatten()
-        .map(|seed| {
-            let r = fertilizer
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
-        })
-        .flatten()
-        .map(|seed| {
-            let r = water
+    let loc = [soil, fertilizer, water, light, temp, humidity, locations]
+        .iter()
+        .fold(seeds, |ranges, triplets| {
+            ranges
                 .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
-        })
-        .flatten()
-        .map(|seed| {
-            let r = light
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
-        })
-        .flatten()
-        .map(|seed| {
-            let r = temp
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
-        })
-        .flatten()
-        .map(|seed| {
-            let r = humidity
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
+                .flat_map(|range| map_range(range, triplets))
+                .collect_vec()
         })
-        .flatten()
+        .into_iter()
+        .map(|seed| seed.0)
+        .min();
+
+    Ok(loc.unwrap())
+}
+
+// naive version of `parse_part1`: walks every single seed through the maps.
+// Only usable on small inputs.
+fn parse_part1_per_seed(input: &mut &str) -> PResult<u64> {
+    let (seeds, soil, fertilizer, water, light, temp, humidity, locations) =
+        parse_map.parse_next(input).expect("Failed to parse map");
+
+    let maps = [soil, fertilizer, water, light, temp, humidity, locations];
+    let loc = seeds
+        .into_iter()
+        .flat_map(|(start, end)| start..=end)
         .map(|seed| {
-            let r = locations
-                .iter()
-                .filter_map(move |triplet| overlap_range_triplet(&seed, triplet))
-                .collect_vec();
-            if r.len() == 0 {
-                return vec![seed];
-            }
-            return r;
+            maps.iter().fold(seed, |val, triplets| {
+                triplets
+                    .iter()
+                    .find_map(|triplet| source_dest_map(val, triplet))
+                    .unwrap_or(val)
+            })
         })
-        .flatten()
-        .inspect(|v| println!("loc ranges are: {:?}", v))
-        .map(|seed| seed.0)
         .min();
 
     Ok(loc.unwrap())
 }
 
+type Solver = fn(&mut &str) -> PResult<u64>;
+
+// every implementation of part 2, `main` runs the first one
+const PART2_SOLVERS: [(&str, Solver); 2] = [
+    ("intervals", parse_part1),
+    ("per_seed", parse_part1_per_seed),
+];
+
 fn main() {
     let result = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
-    let out = parse_part1.parse(&result).unwrap();
+    let (_, mut solver) = PART2_SOLVERS[0];
+    let out = solver.parse(&result).unwrap();
     println!("{}", out);
 }
 
@@ -323,6 +310,100 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crosscheck::next_rand;
+    use snapshot::assert_snapshot;
+
+    const SAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    const MAP_NAMES: [&str; 7] = [
+        "seed-to-soil",
+        "soil-to-fertilizer",
+        "fertilizer-to-water",
+        "water-to-light",
+        "light-to-temperature",
+        "temperature-to-humidity",
+        "humidity-to-location",
+    ];
+
+    // source ranges inside a map never overlap, like the real input
+    fn generate_input(seed: &mut u64) -> String {
+        let seeds: Vec<String> = (0..1 + next_rand(seed) % 3)
+            .map(|_| format!("{} {}", next_rand(seed) % 100, 1 + next_rand(seed) % 20))
+            .collect();
+        let mut out = format!("seeds: {}\n", seeds.join(" "));
+        for name in MAP_NAMES {
+            let mut triplets = Vec::new();
+            let mut source = next_rand(seed) % 10;
+            while source < 100 {
+                let length = 1 + next_rand(seed) % 30;
+                if next_rand(seed) % 3 < 2 {
+                    triplets.push(format!("{} {source} {length}", next_rand(seed) % 100));
+                }
+                source += length + next_rand(seed) % 5;
+            }
+            if triplets.is_empty() {
+                triplets.push(format!("{} {source} 1", next_rand(seed) % 100));
+            }
+            out.push_str(&format!("\n{name} map:\n{}\n", triplets.join("\n")));
+        }
+        out
+    }
+
+    fn assert_solvers_agree(input: &str) {
+        let solvers =
+            PART2_SOLVERS.map(|(name, mut solver)| (name, move |i: &str| solver.parse(i).unwrap()));
+        crosscheck::assert_solvers_agree(input, solvers);
+    }
+
+    #[test]
+    fn test_part2() {
+        for (name, mut solver) in PART2_SOLVERS {
+            assert_eq!(solver.parse(SAMPLE), Ok(46), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_part2_solvers_agree() {
+        assert_solvers_agree(SAMPLE);
+
+        let mut seed = 0x2023_0005;
+        for _ in 0..50 {
+            assert_solvers_agree(&generate_input(&mut seed));
+        }
+    }
 
     #[test]
     fn test_parse_seeds() {
@@ -339,6 +420,14 @@ mod tests {
         assert_eq!(parse_triplet.parse(input), Ok(expected));
     }
 
+    #[test]
+    fn test_parse_map_snapshot() {
+        let (_, soil, fertilizer, water, light, temp, humidity, locations) =
+            parse_map.parse(SAMPLE).unwrap();
+        let maps = [soil, fertilizer, water, light, temp, humidity, locations];
+        assert_snapshot!("sample_sorted_triplets", maps);
+    }
+
     #[test]
     fn test_parse_seeds_to_soil() {
         let input = "seed-to-soil map:\n1 2 3";
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
 
 #[derive(Debug, Eq)]
@@ -57,38 +57,42 @@ impl Hand<'_> {
     }
 }
 
-impl<'a> From<&'a str> for Hand<'a> {
-    fn from(cards: &'a str) -> Self {
-        let mut _cards = cards.chars();
-        let mut card_counts = HashMap::new();
-        for card in _cards {
-            let count = card_counts.entry(card).or_insert(0);
-            *count += 1;
-        }
+// how many of each card a hand has, jokers already added to the most repeated
+// other card
+fn card_counts(cards: &str) -> BTreeMap<char, usize> {
+    let mut card_counts = BTreeMap::new();
+    for card in cards.chars() {
+        let count = card_counts.entry(card).or_insert(0);
+        *count += 1;
+    }
 
-        let mut charx = 'x';
-        let mut new_val = 0;
-        if let Some(count) = card_counts.get(&'J') {
-            let mut max = 0;
-            for (card, ccount) in card_counts.iter() {
-                if card == &'J' {
-                    continue;
-                }
+    let mut charx = 'x';
+    let mut new_val = 0;
+    if let Some(count) = card_counts.get(&'J') {
+        let mut max = 0;
+        for (card, ccount) in card_counts.iter() {
+            if card == &'J' {
+                continue;
+            }
 
-                if ccount > &max {
-                    max = *ccount;
-                    charx = *card;
-                    new_val = *count;
-                }
+            if ccount > &max {
+                max = *ccount;
+                charx = *card;
+                new_val = *count;
             }
         }
-        if charx != 'x' {
-            card_counts.remove(&'J');
-            // let jok = card_counts.entry('J').or_insert(0);
-            // *jok = 0;
-            let other = card_counts.entry(charx).or_insert(0);
-            *other += new_val;
-        }
+    }
+    if charx != 'x' {
+        card_counts.remove(&'J');
+        let other = card_counts.entry(charx).or_insert(0);
+        *other += new_val;
+    }
+    card_counts
+}
+
+impl<'a> From<&'a str> for Hand<'a> {
+    fn from(cards: &'a str) -> Self {
+        let card_counts = card_counts(cards);
 
         let mut counts = card_counts.values().collect::<Vec<_>>();
         counts.sort();
@@ -175,6 +179,29 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use snapshot::assert_snapshot;
+
+    const SAMPLE: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(SAMPLE), 5905);
+    }
+
+    #[test]
+    fn test_card_counts_snapshot() {
+        let counts: Vec<(&str, BTreeMap<char, usize>)> = SAMPLE
+            .lines()
+            .chain(["JJJJJ 1", "J2345 1"])
+            .map(|line| line.split_at(5).0)
+            .map(|hand| (hand, card_counts(hand)))
+            .collect();
+        assert_snapshot!("sample_card_counts", counts);
+    }
 
     #[test]
     fn test_chars_cmp() {
@@ -96,8 +96,95 @@ fn part2(i: &str) -> usize {
     lcm(&steps_comb)
 }
 
+// naive version of part 2: move every ghost at the same time until all of
+// them stand on a `Z` node. Only usable on small inputs.
+fn part2_lockstep(i: &str) -> usize {
+    let (instructions, last) = i.split_once("\n\n").unwrap();
+    let s = parse_lines(last);
+
+    let mut current_locs: Vec<&str> = s.keys().filter(|k| k.ends_with('A')).copied().collect();
+    let mut steps = 0;
+
+    for dir in instructions.chars().cycle() {
+        if current_locs.iter().all(|loc| loc.ends_with('Z')) {
+            break;
+        }
+        for current_loc in current_locs.iter_mut() {
+            let (left, right) = s.get(current_loc).unwrap();
+            *current_loc = match dir {
+                'L' => left,
+                'R' => right,
+                _ => unreachable!("Letter not found"),
+            };
+        }
+        steps += 1;
+    }
+    steps
+}
+
+type Solver = fn(&str) -> usize;
+
+// every implementation of part 2, `main` runs the first one
+const PART2_SOLVERS: [(&str, Solver); 2] = [("lcm", part2), ("lockstep", part2_lockstep)];
+
 fn main() {
     let raw = fs::read_to_string("input.txt").expect("Something went wrong reading the file");
-    let out = part2(raw.trim());
+    let (_, solver) = PART2_SOLVERS[0];
+    let out = solver(raw.trim());
     println!("{out}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crosscheck::{assert_solvers_agree, next_rand};
+
+    const SAMPLE_PART2: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+    // each ghost walks a loop of `len` nodes: `xAA -> x01 -> .. -> xZZ -> x01`,
+    // so the first `Z` is hit after `len` steps and then every `len` steps
+    fn generate_input(seed: &mut u64) -> String {
+        let mut nodes = Vec::new();
+        let ghosts = 1 + next_rand(seed) % 3;
+        for ghost in ["B", "C", "D"].iter().take(ghosts as usize) {
+            let len = 2 + next_rand(seed) % 11;
+            let name = |n: u64| match n {
+                0 => format!("{ghost}AA"),
+                n if n == len => format!("{ghost}ZZ"),
+                n => format!("{ghost}{n:02}"),
+            };
+            for n in 0..len {
+                let next = name(n + 1);
+                nodes.push(format!("{} = ({next}, {next})", name(n)));
+            }
+            nodes.push(format!("{} = ({}, {})", name(len), name(1), name(1)));
+        }
+        format!("LR\n\n{}", nodes.join("\n"))
+    }
+
+    #[test]
+    fn test_part2() {
+        for (name, solver) in PART2_SOLVERS {
+            assert_eq!(solver(SAMPLE_PART2), 6, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_part2_solvers_agree() {
+        assert_solvers_agree(SAMPLE_PART2, PART2_SOLVERS);
+
+        let mut seed = 0x2023_0008;
+        for _ in 0..50 {
+            assert_solvers_agree(&generate_input(&mut seed), PART2_SOLVERS);
+        }
+    }
+}
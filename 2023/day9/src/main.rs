@@ -1,12 +1,25 @@
 use std::fs;
 
-fn series(i: &[i128]) -> i128 {
-    if i.iter().all(|x| x == &0) {
-        return 0;
+// the input followed by each row of differences, down to the all zeros one
+fn difference_table(i: &[i128]) -> Vec<Vec<i128>> {
+    let mut table = vec![i.to_vec()];
+    while !table.last().unwrap().iter().all(|x| x == &0) {
+        let r = table
+            .last()
+            .unwrap()
+            .windows(2)
+            .map(|x| x[1] - x[0])
+            .collect::<Vec<i128>>();
+        table.push(r);
     }
-    let r = i.windows(2).map(|x| x[1] - x[0]).collect::<Vec<i128>>();
+    table
+}
 
-    i.last().unwrap() + series(&r)
+fn series(i: &[i128]) -> i128 {
+    difference_table(i)
+        .iter()
+        .map(|row| row.last().unwrap_or(&0))
+        .sum()
 }
 
 fn part1(i: &str) -> i128 {
@@ -21,12 +34,10 @@ fn part1(i: &str) -> i128 {
 }
 
 fn prev_series(i: &[i128]) -> i128 {
-    if i.iter().all(|x| x == &0) {
-        return 0;
-    }
-    let r = i.windows(2).map(|x| x[0] - x[1]).collect::<Vec<i128>>();
-
-    i.first().unwrap() + prev_series(&r)
+    difference_table(i)
+        .iter()
+        .rev()
+        .fold(0, |acc, row| row.first().unwrap_or(&0) - acc)
 }
 
 fn part2(i: &str) -> i128 {
@@ -49,6 +60,31 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use snapshot::assert_snapshot;
+
+    const SAMPLE: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(part1(SAMPLE), 114);
+        assert_eq!(part2(SAMPLE), 2);
+    }
+
+    #[test]
+    fn test_difference_table_snapshot() {
+        let tables: Vec<Vec<Vec<i128>>> = SAMPLE
+            .lines()
+            .map(|x| {
+                x.split(" ")
+                    .map(|v| v.parse::<i128>().unwrap())
+                    .collect::<Vec<i128>>()
+            })
+            .map(|x| difference_table(&x))
+            .collect();
+        assert_snapshot!("sample_difference_tables", tables);
+    }
 
     #[test]
     fn test_series() {
@@ -0,0 +1,248 @@
+use std::{env, fs, path::Path, process};
+
+#[derive(Debug, PartialEq)]
+struct Metadata {
+    title: String,
+    parts: Vec<u32>,
+    stars: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct Day {
+    year: u32,
+    day: u32,
+    metadata: Metadata,
+}
+
+// reads the `[package.metadata.aoc]` table of a day's Cargo.toml:
+//
+// [package.metadata.aoc]
+// title = "Sonar Sweep"
+// parts = [1, 2]
+// stars = 2
+fn parse_metadata(manifest: &str) -> Option<Metadata> {
+    let mut lines = manifest.lines().map(str::trim);
+    lines.find(|line| *line == "[package.metadata.aoc]")?;
+
+    let mut title = None;
+    let mut parts = None;
+    let mut stars = None;
+    for line in lines.take_while(|line| !line.starts_with('[')) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "title" => title = Some(value.trim_matches('"').to_string()),
+            "parts" => {
+                parts = value
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split(',')
+                    .filter(|part| !part.trim().is_empty())
+                    .map(|part| part.trim().parse::<u32>().ok())
+                    .collect()
+            }
+            "stars" => stars = value.parse::<u32>().ok(),
+            _ => continue,
+        }
+    }
+    Some(Metadata {
+        title: title?,
+        parts: parts?,
+        stars: stars?,
+    })
+}
+
+// every `<year>/day<n>` crate under `root`, sorted by year and day
+fn collect_days(root: &Path) -> Vec<Day> {
+    let mut days = Vec::new();
+    for year_entry in fs::read_dir(root).expect("Something went wrong reading the root") {
+        let year_path = year_entry.unwrap().path();
+        let Some(year) = year_path
+            .file_name()
+            .and_then(|name| name.to_str()?.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        for day_entry in fs::read_dir(&year_path).expect("Something went wrong reading the year") {
+            let day_path = day_entry.unwrap().path();
+            let Some(day) = day_path
+                .file_name()
+                .and_then(|name| name.to_str()?.strip_prefix("day")?.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let manifest = fs::read_to_string(day_path.join("Cargo.toml"))
+                .expect("Something went wrong reading the Cargo.toml");
+            let metadata = parse_metadata(&manifest).unwrap_or_else(|| {
+                panic!("{year}/day{day} is missing [package.metadata.aoc] in its Cargo.toml")
+            });
+            days.push(Day {
+                year,
+                day,
+                metadata,
+            });
+        }
+    }
+    days.sort_by_key(|d| (d.year, d.day));
+    days
+}
+
+fn years(days: &[Day]) -> Vec<u32> {
+    let mut years: Vec<u32> = days.iter().map(|d| d.year).collect();
+    years.dedup();
+    years
+}
+
+fn star_marks(stars: u32) -> &'static str {
+    match stars {
+        0 => ". ",
+        1 => "* ",
+        _ => "**",
+    }
+}
+
+fn render_calendar(days: &[Day]) -> String {
+    let mut out = String::new();
+    for year in years(days) {
+        let year_days: Vec<&Day> = days.iter().filter(|d| d.year == year).collect();
+        let stars: u32 = year_days.iter().map(|d| d.metadata.stars).sum();
+        out.push_str(&format!("{year}  {stars}/50 stars\n"));
+        for week in (1..=25).collect::<Vec<u32>>().chunks(5) {
+            let row: Vec<String> = week
+                .iter()
+                .map(|day| {
+                    let stars = year_days
+                        .iter()
+                        .find(|d| d.day == *day)
+                        .map_or(0, |d| d.metadata.stars);
+                    format!("{day:>4} {}", star_marks(stars))
+                })
+                .collect();
+            out.push_str(&format!("{}\n", row.join("  ").trim_end()));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_readme(days: &[Day]) -> String {
+    let mut out = String::from("# Advent of Code\n\n");
+    out.push_str("Generated from each day's `[package.metadata.aoc]`, do not edit by hand:\n\n");
+    out.push_str("```\ncargo run --manifest-path calendar/Cargo.toml -- readme\n```\n");
+    for year in years(days) {
+        let year_days: Vec<&Day> = days.iter().filter(|d| d.year == year).collect();
+        let stars: u32 = year_days.iter().map(|d| d.metadata.stars).sum();
+        out.push_str(&format!("\n## {year} ({stars} stars)\n\n"));
+        out.push_str("| Day | Title | Parts | Stars |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for d in year_days {
+            let parts: Vec<String> = d.metadata.parts.iter().map(u32::to_string).collect();
+            out.push_str(&format!(
+                "| [{}]({}/day{}) | {} | {} | {} |\n",
+                d.day,
+                d.year,
+                d.day,
+                d.metadata.title,
+                parts.join(", "),
+                star_marks(d.metadata.stars).trim_end(),
+            ));
+        }
+    }
+    out
+}
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("calendar lives inside the repository");
+    let days = collect_days(root);
+
+    match env::args().nth(1).as_deref() {
+        None | Some("calendar") => print!("{}", render_calendar(&days)),
+        Some("readme") => {
+            let path = root.join("README.md");
+            fs::write(&path, render_readme(&days))
+                .expect("Something went wrong writing the README");
+            println!("wrote {}", path.display());
+        }
+        Some(other) => {
+            eprintln!("unknown command {other:?}, expected `calendar` or `readme`");
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: u32, day: u32, parts: Vec<u32>, stars: u32) -> Day {
+        Day {
+            year,
+            day,
+            metadata: Metadata {
+                title: format!("Day {day}"),
+                parts,
+                stars,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let input = r#"[package]
+name = "day10"
+
+[dependencies]
+
+[package.metadata.aoc]
+title = "Pipe Maze"
+parts = [1]
+stars = 1
+"#;
+        let expected = Metadata {
+            title: "Pipe Maze".to_string(),
+            parts: vec![1],
+            stars: 1,
+        };
+        assert_eq!(parse_metadata(input), Some(expected));
+
+        let input = "[package.metadata.aoc]\ntitle = \"Dive!\"\nparts = [1, 2]\nstars = 2";
+        let expected = Metadata {
+            title: "Dive!".to_string(),
+            parts: vec![1, 2],
+            stars: 2,
+        };
+        assert_eq!(parse_metadata(input), Some(expected));
+
+        let input = "[package.metadata.aoc]\ntitle = \"Dive!\"\n\n[dependencies]\nstars = 2";
+        assert_eq!(parse_metadata(input), None);
+        assert_eq!(parse_metadata("[package]\nname = \"day1\""), None);
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let days = vec![day(2023, 1, vec![1, 2], 2), day(2023, 7, vec![1], 1)];
+        let expected = "2023  3/50 stars
+   1 **     2 .      3 .      4 .      5 .
+   6 .      7 *      8 .      9 .     10 .
+  11 .     12 .     13 .     14 .     15 .
+  16 .     17 .     18 .     19 .     20 .
+  21 .     22 .     23 .     24 .     25 .
+
+";
+        assert_eq!(render_calendar(&days), expected);
+    }
+
+    #[test]
+    fn test_render_readme() {
+        let days = vec![day(2021, 1, vec![2], 2), day(2023, 10, vec![1], 1)];
+        let output = render_readme(&days);
+        assert!(output.contains("## 2021 (2 stars)"));
+        assert!(output.contains("| [1](2021/day1) | Day 1 | 2 | ** |"));
+        assert!(output.contains("## 2023 (1 stars)"));
+        assert!(output.contains("| [10](2023/day10) | Day 10 | 1 | * |"));
+    }
+}
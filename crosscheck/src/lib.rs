@@ -0,0 +1,66 @@
+//! Checks that every solver of a day gives the same answer.
+//!
+//! A day keeps its implementations of a part in a `[(name, solver)]` table,
+//! builds random inputs with `next_rand` and hands both to
+//! `assert_solvers_agree`. The first solver is the reference, the others are
+//! compared against it.
+use std::fmt::Debug;
+
+// xorshift, good enough to build inputs without pulling a crate. The seed
+// must not be 0.
+pub fn next_rand(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+#[track_caller]
+pub fn assert_solvers_agree<'a, T, F>(input: &str, solvers: impl IntoIterator<Item = (&'a str, F)>)
+where
+    T: PartialEq + Debug,
+    F: FnMut(&str) -> T,
+{
+    let mut solvers = solvers.into_iter();
+    let (expected_name, mut expected_solver) = solvers.next().expect("no solvers to compare");
+    let expected = expected_solver(input);
+    for (name, mut solver) in solvers {
+        assert_eq!(
+            solver(input),
+            expected,
+            "{name} disagrees with {expected_name} on:\n{input}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_rand() {
+        let mut seed = 1;
+        let first: Vec<u64> = (0..3).map(|_| next_rand(&mut seed)).collect();
+        let mut seed = 1;
+        let again: Vec<u64> = (0..3).map(|_| next_rand(&mut seed)).collect();
+        assert_eq!(first, again);
+        assert!(first.iter().all(|n| *n != 0));
+        assert_ne!(first[0], first[1]);
+    }
+
+    type Solver = fn(&str) -> usize;
+
+    // agree on ascii only
+    const SOLVERS: [(&str, Solver); 2] = [("len", str::len), ("count", |s| s.chars().count())];
+
+    #[test]
+    fn test_assert_solvers_agree() {
+        assert_solvers_agree("ascii", SOLVERS);
+    }
+
+    #[test]
+    #[should_panic(expected = "count disagrees with len on:\nnaïve")]
+    fn test_assert_solvers_disagree() {
+        assert_solvers_agree("naïve", SOLVERS);
+    }
+}
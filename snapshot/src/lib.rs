@@ -0,0 +1,124 @@
+//! Snapshot assertions for the intermediate structures of a day.
+//!
+//! A day dumps a value with `assert_snapshot!("name", value)` from its tests.
+//! The pretty `Debug` output is compared against `snapshots/name.snap` next to
+//! the day's Cargo.toml. When it differs, or no snapshot exists yet, the new
+//! output is written to `snapshots/name.snap.new` for review and the test
+//! fails. Once reviewed, accept it with:
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test
+//! ```
+use std::{env, fmt::Debug, fs, path::Path};
+
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $value:expr) => {
+        $crate::assert_snapshot_in(
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots"),
+            $name,
+            &$value,
+        )
+    };
+}
+
+#[track_caller]
+pub fn assert_snapshot_in(dir: &Path, name: &str, value: &impl Debug) {
+    let actual = format!("{value:#?}\n");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+    if let Err(message) = check(dir, name, &actual, update) {
+        panic!("{message}");
+    }
+}
+
+fn check(dir: &Path, name: &str, actual: &str, update: bool) -> Result<(), String> {
+    let path = dir.join(format!("{name}.snap"));
+    let new_path = dir.join(format!("{name}.snap.new"));
+    fs::create_dir_all(dir).expect("Something went wrong creating the snapshots dir");
+
+    if update {
+        fs::write(&path, actual).expect("Something went wrong writing the snapshot");
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    let Ok(expected) = fs::read_to_string(&path) else {
+        fs::write(&new_path, actual).expect("Something went wrong writing the snapshot");
+        return Err(format!(
+            "no snapshot for {name:?}, review {} and accept it with UPDATE_SNAPSHOTS=1",
+            new_path.display()
+        ));
+    };
+    if expected == actual {
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    fs::write(&new_path, actual).expect("Something went wrong writing the snapshot");
+    let line = expected
+        .lines()
+        .zip(actual.lines())
+        .position(|(e, a)| e != a)
+        .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+    Err(format!(
+        "snapshot {name:?} changed at line {}:\n  - {}\n  + {}\nreview {} and accept it with UPDATE_SNAPSHOTS=1",
+        line + 1,
+        expected.lines().nth(line).unwrap_or(""),
+        actual.lines().nth(line).unwrap_or(""),
+        new_path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn tmp_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("snapshot-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_check_missing() {
+        let dir = tmp_dir("missing");
+        let output = check(&dir, "table", "[1, 2]\n", false);
+        assert!(output.unwrap_err().starts_with("no snapshot for \"table\""));
+        assert_eq!(
+            fs::read_to_string(dir.join("table.snap.new")).unwrap(),
+            "[1, 2]\n"
+        );
+        assert!(!dir.join("table.snap").exists());
+    }
+
+    #[test]
+    fn test_check_update_then_match() {
+        let dir = tmp_dir("update");
+        assert_eq!(check(&dir, "table", "[1, 2]\n", true), Ok(()));
+        assert_eq!(check(&dir, "table", "[1, 2]\n", false), Ok(()));
+        assert!(!dir.join("table.snap.new").exists());
+    }
+
+    #[test]
+    fn test_check_changed() {
+        let dir = tmp_dir("changed");
+        check(&dir, "table", "[\n    1,\n    2,\n]\n", true).unwrap();
+
+        let output = check(&dir, "table", "[\n    1,\n    3,\n]\n", false);
+        assert!(output
+            .unwrap_err()
+            .starts_with("snapshot \"table\" changed at line 3:\n  -     2,\n  +     3,"));
+        assert!(dir.join("table.snap.new").exists());
+
+        check(&dir, "table", "[\n    1,\n    3,\n]\n", true).unwrap();
+        assert!(!dir.join("table.snap.new").exists());
+    }
+
+    #[test]
+    fn test_assert_snapshot_in() {
+        let dir = tmp_dir("assert");
+        check(&dir, "counts", "[\n    3,\n    2,\n]\n", true).unwrap();
+        assert_snapshot_in(&dir, "counts", &vec![3, 2]);
+    }
+}